@@ -0,0 +1,52 @@
+use bevy::prelude::*;
+use std::time::Duration;
+
+pub struct CalendarPlugin;
+
+impl Plugin for CalendarPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Calendar>()
+            .add_system(advance_calendar);
+    }
+}
+
+const DAY_LENGTH: Duration = Duration::from_secs(180);
+pub const DAYS_PER_YEAR: u32 = 28;
+
+#[derive(Resource)]
+pub struct Calendar {
+    // Days since the teahouse opened.
+    pub day: u32,
+    timer: Timer,
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        Self {
+            day: 0,
+            timer: Timer::new(DAY_LENGTH, TimerMode::Repeating),
+        }
+    }
+}
+
+impl Calendar {
+    pub fn day_of_year(&self) -> u32 {
+        self.day % DAYS_PER_YEAR
+    }
+
+    pub fn describe(&self) -> String {
+        format!("Year {}, day {}", self.day / DAYS_PER_YEAR + 1, self.day_of_year() + 1)
+    }
+}
+
+fn advance_calendar(
+    mut calendar: ResMut<Calendar>,
+    time: Res<Time>,
+) {
+    // Only the passing of a day counts as a change worth reacting to.
+    calendar.bypass_change_detection().timer.tick(time.delta());
+    if calendar.timer.just_finished() {
+        calendar.day += 1;
+    }
+}
@@ -1,20 +1,32 @@
 use bevy::prelude::*;
+use bevy::asset::LoadState;
 use crate::GameState;
 use crate::animation::{AtlasAnimationData, AnimationData, AnimData};
+use crate::calendar::Calendar;
 use crate::dialog::show_message_box;
 use crate::entity::{
-    Chair, Door, Reaction, Paused, Affection, Facing, FacingDirection, Prop
+    Chair, Door, Reaction, Paused, Affection, Facing, FacingDirection, Prop, RelationshipStatus,
 };
 use crate::geom::{MapSize, map_to_screen, transform_to_map_pos, HasSize, TILE_SIZE};
-use crate::interaction::{PlayerInteracted, TransferHeldEntity, DropHeldEntity, Interactable};
+use crate::interaction::{PlayerInteracted, DropHeldEntity, Interactable};
 use crate::map::Map;
-use crate::menu::{Menu, TeaRecipe};
+use crate::menu::{Menu, TeaRecipe, EXACT_MATCH, CLOSE_MATCH, LOOSE_MATCH, RECOMMENDATION_PRICE};
+use crate::message_line::{DEFAULT_EXPIRY, StatusEvent};
+use crate::money::{Money, tip};
 use crate::movable::Movable;
 use crate::pathfinding::PathfindTarget;
-use crate::personality::{Personality, Personalities};
-use crate::player::Holding;
-use crate::tea::TeaPot;
-use rand::seq::IteratorRandom;
+use crate::personality::{
+    PersonalityId, Personalities, RelationshipMilestoneEvent, ServedTea, State as PersonalityState,
+    DEFAULT_SPRITE,
+};
+use crate::player::{Holding, Player};
+use crate::recipe_book::BrewRatedEvent;
+use crate::reputation::{LostPatienceEvent, Reputation};
+use crate::tea::{
+    BrewQuality, Cup, Dirty, Freshness, ServingTemperature, TeaPot, WaterQuality,
+};
+use rand::distributions::WeightedError;
+use rand::seq::{IteratorRandom, SliceRandom};
 use rand::Rng;
 use std::collections::HashMap;
 use std::default::Default;
@@ -28,50 +40,335 @@ impl Plugin for CustomerPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_startup_system(init_texture)
+            .add_system(load_personality_textures)
             .add_system(run_looking_for_chair)
             .add_system(run_moving_to_chair)
             .add_system(run_waiting_for_tea)
             .add_system(run_drinking_tea)
             .add_system(run_leave)
+            .add_system(release_chairs)
+            .add_system(run_party_arrivals)
+            .add_system(run_party_departures)
             .add_system(customer_spawner)
             .add_system(spawn_customer_by_door)
             .add_system(interact_with_customers)
             .add_system(persist_affection)
-            .add_event::<NewCustomerEvent>();
+            .add_system(announce_milestones)
+            .add_event::<NewCustomerEvent>()
+            .add_event::<PartyArrivalEvent>();
     }
 }
 
+// How often a customer asks the player for a recommendation rather than
+// ordering from the menu.
+const RECOMMENDATION_CHANCE: f64 = 0.25;
+
+// How long an average customer will wait for their tea before giving up.
+const PATIENCE: Duration = Duration::from_secs(90);
+const PATIENCE_BAR_WIDTH: f32 = TILE_SIZE * 0.8;
+const PATIENCE_BAR_HEIGHT: f32 = 4.;
+// How long a customer will wait by the door for a seat to become free.
+const WAIT_FOR_CHAIR: Duration = Duration::from_secs(20);
+
+// How often customers arrive with friends, and how many they bring.
+const PARTY_CHANCE: f64 = 0.3;
+const MIN_PARTY_SIZE: usize = 2;
+const MAX_PARTY_SIZE: usize = 4;
+// Members of a party come through the door one after another.
+const PARTY_ARRIVAL_INTERVAL: Duration = Duration::from_secs(2);
+
+// Happy customers bring friends more often, while unhappy ones come alone.
+fn party_chance(status: RelationshipStatus) -> f64 {
+    match status {
+        RelationshipStatus::Angry => 0.,
+        RelationshipStatus::Neutral => PARTY_CHANCE,
+        RelationshipStatus::Friendly => PARTY_CHANCE * 1.5,
+        RelationshipStatus::VeryFriendly | RelationshipStatus::Crushing => PARTY_CHANCE * 2.,
+    }
+}
+
+fn patience_time(patience: f32, affection: &Affection) -> f32 {
+    // Customers who like the player are willing to wait longer.
+    let relationship = match affection.status() {
+        RelationshipStatus::Angry => 0.75,
+        RelationshipStatus::Neutral => 1.,
+        RelationshipStatus::Friendly => 1.25,
+        RelationshipStatus::VeryFriendly => 1.5,
+        RelationshipStatus::Crushing => 2.,
+    };
+    PATIENCE.as_secs_f32() * patience * relationship
+}
+
 impl Customer {
-    fn conversation(&self) -> Vec<String> {
-        return vec![
-            "You: Welcome to Sereni Tea!".to_owned(),
-            format!("{:?}: Thank you.", self.personality),
-            format!("{:?}: I would like the {}, please.", self.personality, self.expected.name),
-            "You: Coming right up!.".to_owned(),
-        ];
+    // Marks the birthday as over, returning whether it passed without anyone noticing.
+    fn forget_birthday(&mut self) -> bool {
+        let forgotten = self.birthday && !self.celebrated;
+        self.celebrated = self.birthday;
+        forgotten
     }
 
-    fn tea_delivery(&self, teapot: &TeaPot) -> (Reaction, Vec<String>) {
-        let hint = teapot
-            .ingredients
-            .iter()
-            .max_by_key(|(_ingredient, amount)| *amount)
-            .unwrap()
-            .0;
+    fn greeting(&self, state: &PersonalityState) -> [String; 2] {
+        let name = &self.personality;
+        if state.visits <= 1 {
+            return [
+                "You: Welcome to Sereni Tea!".to_owned(),
+                format!("{}: Thank you. What a charming little place.", name),
+            ];
+        }
+        match state.affection.status() {
+            RelationshipStatus::Angry => [
+                "You: Oh. Welcome back.".to_owned(),
+                format!("{}: I suppose I'll give this place one more chance.", name),
+            ],
+            RelationshipStatus::Neutral if state.is_regular() => [
+                format!("You: Welcome back, {}.", name),
+                format!("{}: Thank you. The usual seat, I think.", name),
+            ],
+            RelationshipStatus::Neutral => [
+                "You: Welcome back to Sereni Tea!".to_owned(),
+                format!("{}: Thank you.", name),
+            ],
+            RelationshipStatus::Friendly => [
+                format!("You: {}! Good to see you again.", name),
+                format!("{}: Likewise! I've been looking forward to this all week.", name),
+            ],
+            RelationshipStatus::VeryFriendly => [
+                format!("You: {}! I saved your favourite seat.", name),
+                format!("{}: You always know how to brighten my day.", name),
+            ],
+            RelationshipStatus::Crushing => [
+                format!("You: {}! I was hoping you'd drop by today.", name),
+                format!("{}: I, um... may have walked past a few times before coming in.", name),
+            ],
+        }
+    }
+
+    fn last_tea_line(&self) -> Option<String> {
+        let last_tea = self.last_tea.as_ref()?;
+        let tea = last_tea.describe();
+        Some(format!("{}: {}", self.personality, match last_tea.reaction {
+            Reaction::MajorPositive =>
+                format!("I still think about {} you made me last time.", tea),
+            Reaction::Positive =>
+                format!("I quite enjoyed {} last time.", tea),
+            Reaction::Negative =>
+                format!("I hope today goes better than {} last time.", tea),
+            Reaction::MajorNegative =>
+                format!("I'm willing to forget about {} last time. Just this once.", tea),
+        }))
+    }
 
+    fn conversation(&self, state: &PersonalityState, party: &PartyOrder) -> Vec<String> {
+        let mut rng = rand::thread_rng();
+        let mut conversation = self.greeting(state).to_vec();
+        if let Some(line) = self.last_tea_line() {
+            conversation.push(line);
+        }
+        if let Some(line) = state.dialogue.choose(&mut rng) {
+            conversation.push(format!("{}: {}", self.personality, line));
+        }
+        let usual = self.last_tea
+            .as_ref()
+            .filter(|tea| tea.reaction == Reaction::MajorPositive)
+            .and_then(|tea| tea.recipe.as_ref());
+        let trusting = state.affection.status() >= RelationshipStatus::VeryFriendly;
+        let waiting = matches!(party, PartyOrder::Waiting);
+        let order = match (&self.ordered_by, &self.expected) {
+            (Some(leader), _) =>
+                format!("{}: {} is ordering for all of us.", self.personality, leader),
+            _ if waiting => format!(
+                "{}: We'll order once everybody in our party has sat down.",
+                self.personality,
+            ),
+            (None, Some(recipe)) if usual == Some(&recipe.name) => format!(
+                "{}: The {} again, please. You made it perfectly.",
+                self.personality,
+                recipe.name,
+            ),
+            (None, Some(recipe)) =>
+                format!("{}: I would like the {}, please.", self.personality, recipe.name),
+            (None, None) if trusting => format!(
+                "{}: I'll have whatever you think I'd like. I trust you.",
+                self.personality,
+            ),
+            (None, None) =>
+                format!("{}: What would you recommend? Surprise me!", self.personality),
+        };
+        conversation.push(order);
+        if let PartyOrder::Ready(companions) = party {
+            for (companion, order) in companions {
+                conversation.push(
+                    format!("{}: And {} would like {}.", self.personality, companion, order)
+                );
+            }
+        }
+        if self.ordered_by.is_none() && !waiting {
+            conversation.push("You: Coming right up!.".to_owned());
+        }
+        if self.birthday && !self.celebrated {
+            conversation.push(
+                format!("{}: Today's a rather special day for me, you know.", self.personality)
+            );
+        }
+        conversation
+    }
+
+    fn order(&self) -> String {
+        match &self.expected {
+            Some(recipe) => format!("the {}", recipe.name),
+            None => "whatever you recommend".to_owned(),
+        }
+    }
+
+    fn tea_delivery(
+        &self,
+        teapot: &TeaPot,
+        preferences: &PersonalityState,
+    ) -> (Reaction, Vec<String>) {
         let mut conversation = vec![
             "You: Here's your tea.".to_owned(),
-            format!("{:?}: Oh, thank you!", self.personality),
-            format!("{:?}: Is that a hint of {:?}?", self.personality, hint),
+            format!("{}: Oh, thank you!", self.personality),
+            format!("{}: {}", self.personality, teapot.flavour().describe()),
             "You: Enjoy!".to_owned(),
         ];
-        let recipe_ingredients = HashMap::from_iter(self.expected.ingredients.clone().into_iter());
-        let reaction = if recipe_ingredients != teapot.ingredients {
-            conversation.push("Customer: Wait a minute! This isn't what I ordered.".to_owned());
-            Reaction::Negative
-        } else {
-            conversation.push("Customer: This is exactly what I was hoping for.".to_owned());
-            Reaction::Positive
+        let quality = teapot.brew_quality().unwrap_or(BrewQuality::Perfect);
+        conversation.push(match quality {
+            BrewQuality::UnderSteeped =>
+                format!("{}: It's a little weak, isn't it?", self.personality),
+            BrewQuality::Perfect =>
+                format!("{}: It's steeped just right.", self.personality),
+            BrewQuality::Bitter =>
+                format!("{}: Oof, this has gone quite bitter.", self.personality),
+        });
+
+        let water = teapot.water_quality().unwrap_or(WaterQuality::Ideal);
+        match water {
+            WaterQuality::TooCool => conversation.push(
+                format!("{}: The water wasn't really hot enough for this.", self.personality)
+            ),
+            WaterQuality::TooHot => conversation.push(
+                format!("{}: Hmm, the leaves taste a little scalded.", self.personality)
+            ),
+            WaterQuality::Ideal => (),
+        }
+
+        let serving = teapot.serving_temperature().unwrap_or(ServingTemperature::Hot);
+        match serving {
+            ServingTemperature::Lukewarm => conversation.push(
+                format!("{}: It's gone a bit lukewarm.", self.personality)
+            ),
+            ServingTemperature::Cold => conversation.push(
+                format!("{}: This tea is stone cold!", self.personality)
+            ),
+            ServingTemperature::Hot => (),
+        }
+
+        let freshness = teapot.freshness();
+        match freshness {
+            Freshness::Stale => conversation.push(
+                format!("{}: Are these ingredients a little past their best?", self.personality)
+            ),
+            Freshness::Spoiled => conversation.push(
+                format!("{}: Ugh, something in this has gone off!", self.personality)
+            ),
+            Freshness::Fresh => (),
+        }
+
+        let flavour = teapot.flavour();
+        let tastes = flavour.dominant();
+        let similarity = self.expected
+            .as_ref()
+            .map(|recipe| recipe.similarity(&teapot.ingredients, &teapot.additions));
+        let liked = preferences.likes
+            .iter()
+            .find(|ingredient| teapot.ingredients.contains_key(*ingredient));
+        let (reaction, line) = match similarity {
+            // Without an order to compare against, a balanced brew or a favourite
+            // ingredient is what impresses.
+            None => {
+                let balanced = tastes.len() >= 2 &&
+                    flavour.get(tastes[1]) >= flavour.get(tastes[0]) / 2.;
+                if let Some(ingredient) = liked {
+                    (Reaction::MajorPositive, format!("Oh, {:?}! You know me so well.", ingredient))
+                } else if balanced {
+                    (Reaction::MajorPositive, "What a lovely combination!".to_owned())
+                } else {
+                    (Reaction::Positive, "Not bad at all.".to_owned())
+                }
+            }
+            Some(similarity) if similarity >= EXACT_MATCH =>
+                (Reaction::MajorPositive, "This is exactly what I was hoping for.".to_owned()),
+            Some(similarity) if similarity >= CLOSE_MATCH =>
+                (Reaction::Positive, "This is close to what I ordered.".to_owned()),
+            Some(similarity) if similarity >= LOOSE_MATCH =>
+                (Reaction::Negative, "Hmm, this isn't quite what I ordered.".to_owned()),
+            Some(_) => (
+                Reaction::MajorNegative,
+                "Wait a minute! This isn't what I ordered at all.".to_owned(),
+            ),
+        };
+        conversation.push(format!("{}: {}", self.personality, line));
+        if let Some(recipe) = &self.expected {
+            if !recipe.additions_in_order(&teapot.additions) {
+                conversation.push(
+                    format!("{}: Those were stirred in the wrong order, though.", self.personality)
+                );
+            }
+        }
+
+        let disliked = preferences.dislikes
+            .iter()
+            .find(|ingredient| teapot.ingredients.contains_key(*ingredient));
+        if let Some(ingredient) = disliked {
+            conversation.push(
+                format!("{}: Is there {:?} in this? I can't stand it.", self.personality, ingredient)
+            );
+        }
+
+        let reaction = match quality {
+            BrewQuality::Perfect => reaction,
+            BrewQuality::UnderSteeped => reaction.worsen(),
+            BrewQuality::Bitter => reaction.worsen().worsen(),
+        };
+        let reaction = match water {
+            WaterQuality::Ideal => reaction,
+            WaterQuality::TooCool | WaterQuality::TooHot => reaction.worsen(),
+        };
+        let reaction = match freshness {
+            Freshness::Fresh => reaction,
+            Freshness::Stale => reaction.worsen(),
+            Freshness::Spoiled => reaction.worsen().worsen(),
+        };
+        let reaction = match serving {
+            ServingTemperature::Hot => reaction,
+            ServingTemperature::Lukewarm => reaction.worsen(),
+            // Nobody is happy to be served cold tea.
+            ServingTemperature::Cold => match reaction.worsen() {
+                Reaction::MajorPositive | Reaction::Positive => Reaction::Negative,
+                reaction => reaction,
+            },
+        };
+        // Even a perfect match for the order can't make up for an ingredient they hate.
+        let reaction = match (disliked, reaction) {
+            (Some(_), Reaction::MajorPositive | Reaction::Positive) => Reaction::Negative,
+            _ => reaction,
+        };
+        let reaction = match (self.birthday && !self.celebrated, liked) {
+            (false, _) => reaction,
+            (true, Some(_)) if disliked.is_none() => {
+                conversation.push(format!(
+                    "{}: My favourite, on my birthday! You remembered!",
+                    self.personality,
+                ));
+                Reaction::MajorPositive
+            }
+            (true, _) => {
+                conversation.push(format!(
+                    "{}: ...It's my birthday today, you know. Never mind.",
+                    self.personality,
+                ));
+                reaction.worsen()
+            }
         };
         (reaction, conversation)
     }
@@ -81,82 +378,288 @@ impl Customer {
 struct State<T>(pub T);
 
 #[derive(Component)]
-struct LookingForChair;
+struct LookingForChair {
+    timer: Timer,
+    // Chairs this customer already failed to reach.
+    unreachable: Vec<Entity>,
+}
+
+impl LookingForChair {
+    fn new() -> Self {
+        LookingForChair {
+            timer: Timer::new(WAIT_FOR_CHAIR, TimerMode::Once),
+            unreachable: vec![],
+        }
+    }
+}
+
+// Keeps the search going in case the chair turns out to be unreachable.
+#[derive(Component)]
+struct MovingToChair(LookingForChair);
 
+// The chair a customer has reserved for the duration of their visit.
 #[derive(Component)]
-struct MovingToChair;
+struct ClaimedChair(Entity);
 
 #[derive(Component)]
-struct WaitingForTea;
+struct WaitingForTea {
+    // The fraction of the customer's patience that remains.
+    patience: f32,
+    patience_bar: Entity,
+}
+
+#[derive(Component)]
+struct PatienceBar;
 
 #[derive(Component)]
 struct DrinkingTea(Timer);
 
+// Finished, but staying until the rest of the party is ready to go.
+#[derive(Component)]
+struct WaitingForParty;
+
 #[derive(Component)]
 struct Leaving;
 
+// Customers who arrive together, sit at the same table and leave together.
+#[derive(Component)]
+struct Party {
+    size: usize,
+    arrived: usize,
+    // Whether chairs around a table have been reserved for the whole party.
+    reserved: bool,
+    // The table they're sitting at, and reserved chairs there that nobody has
+    // sat down in yet.
+    table: Option<usize>,
+    seats: Vec<Entity>,
+    arrival: Timer,
+}
+
+impl Party {
+    fn new(size: usize) -> Self {
+        Party {
+            size,
+            arrived: 1,
+            reserved: false,
+            table: None,
+            seats: vec![],
+            arrival: Timer::new(PARTY_ARRIVAL_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+#[derive(Component)]
+struct PartyMember {
+    party: Entity,
+    // The first member through the door, who chooses the table and orders.
+    leader: bool,
+}
+
+// What a customer orders on behalf of the rest of their party.
+enum PartyOrder {
+    // Customers on their own, or in a party they aren't leading.
+    Solo,
+    // Some of the party haven't sat down yet.
+    Waiting,
+    // Everybody is seated; what each of the others would like.
+    Ready(Vec<(PersonalityId, String)>),
+}
+
+// Sent when the next member of a party walks through the door.
+struct PartyArrivalEvent(Entity);
+
+// Free chairs for a party of the given size, all at the same table.
+// Customers on their own will sit anywhere.
+fn find_seats(
+    chairs: &Query<(Entity, &mut Chair)>,
+    party_size: usize,
+    unreachable: &[Entity],
+) -> Option<Vec<Entity>> {
+    let mut rng = rand::thread_rng();
+    let free = chairs
+        .iter()
+        .filter(|(entity, chair)| chair.occupant.is_none() && !unreachable.contains(entity));
+    if party_size == 1 {
+        return free.map(|(entity, _)| vec![entity]).choose(&mut rng);
+    }
+    let mut tables: HashMap<usize, Vec<Entity>> = HashMap::new();
+    for (entity, chair) in free {
+        if let Some(table) = chair.table {
+            tables.entry(table).or_default().push(entity);
+        }
+    }
+    tables
+        .into_values()
+        .filter(|seats| seats.len() >= party_size)
+        .choose(&mut rng)
+        .map(|seats| seats.into_iter().take(party_size).collect())
+}
+
 fn run_looking_for_chair(
-    customers: Query<(
-        Entity, Option<&PathfindTarget>
+    mut customers: Query<(
+        Entity, &mut Customer, &mut Affection, &mut State<LookingForChair>, Option<&PartyMember>
     ),(
-        With<Customer>, With<State<LookingForChair>>, Without<Paused>
+        Without<Paused>,
     )>,
-    chairs: Query<(Entity, &Transform, &HasSize), With<Chair>>,
+    mut parties: Query<&mut Party>,
+    mut chairs: Query<(Entity, &mut Chair)>,
+    doors: Query<Entity, With<Door>>,
+    mut status_events: EventWriter<StatusEvent>,
     mut commands: Commands,
+    time: Res<Time>,
 ) {
-    for (customer_entity, target) in &customers {
-        if target.is_none() {
-            let mut rng = rand::thread_rng();
-            let chair_entity = chairs
-                .iter()
-                .map(|(entity, _, _)| entity)
-                .choose(&mut rng)
-                .unwrap();
+    for (customer_entity, mut customer, mut affection, mut state, membership) in &mut customers {
+        let party_entity = membership.map(|member| member.party);
+        let party = party_entity.and_then(|party| parties.get_mut(party).ok());
+        let leading = membership.map_or(false, |member| member.leader);
+        let reserved = party.as_ref().map_or(false, |party| party.reserved);
+        let free_chair = match party {
+            // The leader looks for a table with room for everybody.
+            Some(mut party) if leading && !reserved => {
+                match find_seats(&chairs, party.size, &state.unreachable) {
+                    Some(seats) => {
+                        for seat in &seats {
+                            chairs.get_mut(*seat).unwrap().1.occupant = party_entity;
+                        }
+                        party.table = chairs.get(seats[0]).unwrap().1.table;
+                        party.seats = seats;
+                        party.reserved = true;
+                        party.seats.pop()
+                    }
+                    None => None,
+                }
+            }
+            // Everybody else sits at their party's table, in any seat they can reach.
+            Some(mut party) => {
+                match party.seats.iter().position(|seat| !state.unreachable.contains(seat)) {
+                    Some(index) => Some(party.seats.remove(index)),
+                    None => chairs
+                        .iter()
+                        .find(|(entity, chair)| {
+                            chair.occupant.is_none() &&
+                                chair.table == party.table &&
+                                !state.unreachable.contains(entity)
+                        })
+                        .map(|(entity, _)| entity),
+                }
+            }
+            None => find_seats(&chairs, 1, &state.unreachable)
+                .and_then(|seats| seats.into_iter().next()),
+        };
+        if let Some(chair_entity) = free_chair {
+            chairs.get_mut(chair_entity).unwrap().1.occupant = Some(customer_entity);
+            let search = std::mem::replace(&mut state.0, LookingForChair::new());
             commands.entity(customer_entity)
+                .remove::<State<LookingForChair>>()
+                .insert(State(MovingToChair(search)))
+                .insert(ClaimedChair(chair_entity))
                 .insert(PathfindTarget::new(chair_entity, true));
+            continue;
+        }
+
+        // The teahouse is full, so wait by the door in case a seat frees up.
+        state.timer.tick(time.delta());
+        if !state.timer.finished() {
+            continue;
+        }
+        // A leader without a table takes the rest of the party with them.
+        let abandoning = leading && !reserved;
+        let mut message = if abandoning {
+            format!(
+                "{} couldn't find a table for their party, and will come back another time.",
+                customer.personality,
+            )
         } else {
-            commands.entity(customer_entity)
-                .remove::<State<LookingForChair>>()
-                .insert(State(MovingToChair));
+            format!(
+                "{} couldn't find a free seat, and will come back another time.",
+                customer.personality,
+            )
+        };
+        if customer.forget_birthday() {
+            affection.react(Reaction::Negative);
+            message += " And on their birthday, too.";
+        }
+        status_events.send(StatusEvent::timed_message(
+            customer_entity,
+            message,
+            DEFAULT_EXPIRY,
+        ));
+        commands.entity(customer_entity).remove::<State<LookingForChair>>();
+        match membership {
+            Some(member) if abandoning => {
+                commands.entity(member.party).despawn();
+                start_leaving(&mut commands, customer_entity, &doors);
+            }
+            _ => finish_visit(&mut commands, customer_entity, membership, &doors),
+        }
+    }
+}
+
+fn release_chairs(
+    customers: Query<(Entity, &ClaimedChair), With<State<Leaving>>>,
+    mut chairs: Query<&mut Chair>,
+    mut commands: Commands,
+) {
+    for (customer_entity, claimed) in &customers {
+        if let Ok(mut chair) = chairs.get_mut(claimed.0) {
+            chair.occupant = None;
         }
+        commands.entity(customer_entity).remove::<ClaimedChair>();
     }
 }
 
 fn run_moving_to_chair(
     mut customers: Query<(
-        Entity, Option<&PathfindTarget>, &Transform, &HasSize, &mut Facing,
+        Entity, Option<&PathfindTarget>, &Transform, &HasSize, &mut Facing, &ClaimedChair,
+        &mut State<MovingToChair>,
     ), (
-        With<Customer>, With<State<MovingToChair>>, Without<Paused>
+        With<Customer>, Without<Paused>
     )>,
-    chairs: Query<(&Transform, &HasSize), With<Chair>>,
+    mut chairs: Query<(&Transform, &HasSize, &mut Chair)>,
     props: Query<&Transform, (With<Prop>, With<Movable>)>,
     map: Res<Map>,
     mut commands: Commands,
 ) {
-    for (customer_entity, target, transform, sized, mut facing) in &mut customers {
+    for (customer_entity, target, transform, sized, mut facing, claimed, mut state) in &mut customers {
         if target.is_some() {
             continue;
         }
 
-        // Verify that we made it to a chair and didn't just give up.
+        // Verify that we made it to our chair and didn't just give up.
         let current_pos = transform_to_map_pos(&transform, &map, &sized.size);
-        let mut on_chair = false;
-        for (chair_transform, chair_size) in &chairs {
-            let chair_pos = transform_to_map_pos(&chair_transform, &map, &chair_size.size);
-            if chair_pos == current_pos {
-                on_chair = true;
-                break;
-            }
-        }
-
-        let mut command_builder = commands.entity(customer_entity);
-        command_builder.remove::<State<MovingToChair>>();
-        if !on_chair {
-            command_builder.insert(State(LookingForChair));
+        let (chair_transform, chair_size, mut chair) = chairs.get_mut(claimed.0).unwrap();
+        let chair_pos = transform_to_map_pos(&chair_transform, &map, &chair_size.size);
+        if chair_pos != current_pos {
+            chair.occupant = None;
+            // Carry on looking, without starting the wait over again.
+            let mut search = std::mem::replace(&mut state.0.0, LookingForChair::new());
+            search.unreachable.push(claimed.0);
+            commands.entity(customer_entity)
+                .remove::<State<MovingToChair>>()
+                .remove::<ClaimedChair>()
+                .insert(State(search));
             continue;
         }
 
-        command_builder.insert(State(WaitingForTea));
+        let patience_bar = commands.spawn((
+            PatienceBar,
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::GREEN,
+                    custom_size: Some(Vec2::new(PATIENCE_BAR_WIDTH, PATIENCE_BAR_HEIGHT)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0., TILE_SIZE / 2. + PATIENCE_BAR_HEIGHT, 0.1),
+                ..default()
+            },
+        )).id();
+        commands.entity(customer_entity)
+            .remove::<State<MovingToChair>>()
+            .add_child(patience_bar)
+            .insert(State(WaitingForTea {
+                patience: 1.,
+                patience_bar,
+            }));
 
         // Ensure the customer is facing an appropriate direction for a table,
         // not just the last one they were moving.
@@ -182,30 +685,95 @@ fn run_moving_to_chair(
 
 fn run_waiting_for_tea(
     mut customers: Query<(
-        Entity, Option<&Holding>, &Facing, &mut AnimationData
+        Entity, &mut Customer, &mut Affection, &mut State<WaitingForTea>, Option<&Holding>, &Facing,
+        &mut AnimationData, Option<&PartyMember>,
     ), (
-        With<Customer>, With<State<WaitingForTea>>, Without<Paused>
+        Without<Paused>,
     )>,
+    mut patience_bars: Query<&mut Sprite, With<PatienceBar>>,
+    doors: Query<Entity, With<Door>>,
+    personalities: Res<Personalities>,
+    mut status_events: EventWriter<StatusEvent>,
+    mut lost_patience_events: EventWriter<LostPatienceEvent>,
     mut commands: Commands,
+    time: Res<Time>,
 ) {
-    for (customer_entity, holding, facing, mut animation) in &mut customers {
+    for (
+        customer_entity, mut customer, mut affection, mut state, holding, facing, mut animation,
+        membership,
+    ) in &mut customers {
         let anim_state = standing_conversion(facing.0);
         if !animation.is_current(anim_state) {
             animation.set_current(anim_state);
         }
         if holding.is_some() {
+            commands.entity(state.patience_bar).despawn_recursive();
             commands.entity(customer_entity)
                 .remove::<State<WaitingForTea>>()
                 .insert(State(DrinkingTea(
                     Timer::new(Duration::from_secs(5), TimerMode::Once)
                 )));
+            continue;
+        }
+
+        let patience = personalities.data[&customer.personality].patience;
+        let drain = time.delta_seconds() / patience_time(patience, &affection);
+        state.patience = (state.patience - drain).max(0.);
+        if let Ok(mut sprite) = patience_bars.get_mut(state.patience_bar) {
+            sprite.custom_size = Some(
+                Vec2::new(PATIENCE_BAR_WIDTH * state.patience, PATIENCE_BAR_HEIGHT)
+            );
+            sprite.color = Color::rgb(1. - state.patience, state.patience, 0.);
+        }
+        if state.patience > 0. {
+            continue;
+        }
+
+        affection.react(Reaction::MajorNegative);
+        let mut message = format!("{} got tired of waiting and left.", customer.personality);
+        if customer.forget_birthday() {
+            affection.react(Reaction::Negative);
+            message += " And on their birthday, too.";
         }
+        status_events.send(StatusEvent::timed_message(
+            customer_entity,
+            message,
+            DEFAULT_EXPIRY,
+        ));
+        lost_patience_events.send(LostPatienceEvent);
+        commands.entity(state.patience_bar).despawn_recursive();
+        commands.entity(customer_entity).remove::<State<WaitingForTea>>();
+        finish_visit(&mut commands, customer_entity, membership, &doors);
     }
 }
 
+// Customers in a party wait for their companions before heading out.
+fn finish_visit(
+    commands: &mut Commands,
+    customer_entity: Entity,
+    membership: Option<&PartyMember>,
+    doors: &Query<Entity, With<Door>>,
+) {
+    match membership {
+        Some(_) => {
+            commands.entity(customer_entity).insert(State(WaitingForParty));
+        }
+        None => start_leaving(commands, customer_entity, doors),
+    }
+}
+
+fn start_leaving(commands: &mut Commands, customer_entity: Entity, doors: &Query<Entity, With<Door>>) {
+    let mut rng = rand::thread_rng();
+    let door_entity = doors.iter().choose(&mut rng).unwrap();
+    commands.entity(customer_entity)
+        .insert(State(Leaving))
+        .insert(PathfindTarget::new(door_entity, true));
+}
+
 fn run_drinking_tea(
     mut customers: Query<(
-        Entity, &Facing, &mut AnimationData, &mut State<DrinkingTea>
+        Entity, &Facing, &mut AnimationData, &mut State<DrinkingTea>, Option<&Holding>,
+        Option<&PartyMember>,
     ), (
         With<Customer>, Without<Paused>,
     )>,
@@ -214,28 +782,76 @@ fn run_drinking_tea(
     mut drop_events: EventWriter<DropHeldEntity>,
     time: Res<Time>,
 ) {
-    for (customer_entity, facing, mut animation, mut state) in &mut customers {
+    for (customer_entity, facing, mut animation, mut state, holding, membership) in &mut customers {
         let anim_state = standing_conversion(facing.0);
         if !animation.is_current(anim_state) {
             animation.set_current(anim_state);
         }
-        state.0.0.tick(time.delta());
-        if !state.0.0.finished() {
+        state.timer.tick(time.delta());
+        if !state.timer.finished() {
             continue;
         }
 
-        commands.entity(customer_entity)
-            .remove::<State<DrinkingTea>>()
-            .insert(State(Leaving));
+        commands.entity(customer_entity).remove::<State<DrinkingTea>>();
 
+        // Whatever the customer was drinking from is left behind for the player to clean up.
+        if let Some(holding) = holding {
+            commands.entity(holding.entity).insert(Dirty);
+        }
         drop_events.send(DropHeldEntity {
             holder: customer_entity,
         });
+        finish_visit(&mut commands, customer_entity, membership, &doors);
+    }
+}
 
-        let mut rng = rand::thread_rng();
-        let door_entity = doors.iter().choose(&mut rng).unwrap();
-        commands.entity(customer_entity)
-            .insert(PathfindTarget::new(door_entity, true));
+fn run_party_arrivals(
+    mut parties: Query<(Entity, &mut Party)>,
+    mut arrival_events: EventWriter<PartyArrivalEvent>,
+    time: Res<Time>,
+) {
+    for (party_entity, mut party) in &mut parties {
+        // The rest of the party only comes in once there's a table for them.
+        if !party.reserved || party.arrived >= party.size {
+            continue;
+        }
+        party.arrival.tick(time.delta());
+        if party.arrival.just_finished() {
+            party.arrived += 1;
+            arrival_events.send(PartyArrivalEvent(party_entity));
+        }
+    }
+}
+
+fn run_party_departures(
+    parties: Query<(Entity, &Party)>,
+    members: Query<(Entity, &PartyMember, Option<&State<WaitingForParty>>)>,
+    mut chairs: Query<&mut Chair>,
+    doors: Query<Entity, With<Door>>,
+    mut commands: Commands,
+) {
+    for (party_entity, party) in &parties {
+        if party.arrived < party.size {
+            continue;
+        }
+        let members = members
+            .iter()
+            .filter(|(_, member, _)| member.party == party_entity)
+            .collect::<Vec<_>>();
+        if members.iter().any(|(_, _, waiting)| waiting.is_none()) {
+            continue;
+        }
+        for (member_entity, _, _) in members {
+            commands.entity(member_entity).remove::<State<WaitingForParty>>();
+            start_leaving(&mut commands, member_entity, &doors);
+        }
+        // Seats held for anybody who never turned up are free again.
+        for seat in &party.seats {
+            if let Ok(mut chair) = chairs.get_mut(*seat) {
+                chair.occupant = None;
+            }
+        }
+        commands.entity(party_entity).despawn();
     }
 }
 
@@ -264,10 +880,46 @@ fn persist_affection(
     }
 }
 
+fn announce_milestones(
+    mut milestone_events: EventReader<RelationshipMilestoneEvent>,
+    customers: Query<(Entity, &Customer)>,
+    mut status_events: EventWriter<StatusEvent>,
+) {
+    for event in milestone_events.iter() {
+        let message = match event.status {
+            RelationshipStatus::Friendly =>
+                format!("{} is starting to think of you as a friend.", event.personality),
+            RelationshipStatus::VeryFriendly =>
+                format!("{} counts you among their closest friends.", event.personality),
+            RelationshipStatus::Crushing =>
+                format!("{} can't stop smiling whenever you're around.", event.personality),
+            RelationshipStatus::Angry | RelationshipStatus::Neutral => continue,
+        };
+        let customer = customers
+            .iter()
+            .find(|(_, customer)| customer.personality == event.personality);
+        if let Some((customer_entity, _)) = customer {
+            status_events.send(StatusEvent::timed_message(
+                customer_entity,
+                message,
+                DEFAULT_EXPIRY,
+            ));
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct Customer {
-    pub expected: TeaRecipe,
-    pub personality: Personality,
+    // None when the customer asked for a recommendation.
+    pub expected: Option<TeaRecipe>,
+    pub personality: PersonalityId,
+    // The tea they were served on their previous visit, if any.
+    pub last_tea: Option<ServedTea>,
+    // The member of this customer's party who orders for everyone.
+    pub ordered_by: Option<PersonalityId>,
+    // Regulars visiting on their birthday hope that the player remembers.
+    pub birthday: bool,
+    pub celebrated: bool,
 }
 
 pub struct SpawnerState {
@@ -277,7 +929,7 @@ pub struct SpawnerState {
 impl Default for SpawnerState {
     fn default() -> Self {
         Self {
-            customer_timer: create_customer_timer()
+            customer_timer: create_customer_timer(1.)
         }
     }
 }
@@ -285,10 +937,11 @@ impl Default for SpawnerState {
 const MIN_SPAWN_TIME: u64 = 30;
 const MAX_SPAWN_TIME: u64 = 60;
 
-fn create_customer_timer() -> Timer {
+// A busier teahouse sees customers arrive more often.
+fn create_customer_timer(busyness: f32) -> Timer {
     let mut rng = rand::thread_rng();
     let secs = rng.gen_range(MIN_SPAWN_TIME..MAX_SPAWN_TIME);
-    Timer::new(Duration::from_secs(secs), TimerMode::Once)
+    Timer::from_seconds(secs as f32 / busyness, TimerMode::Once)
 }
 
 #[derive(Copy, Clone)]
@@ -314,16 +967,28 @@ pub struct NewCustomerEvent;
 fn spawn_customer_by_door(
     doors: Query<(&Transform, &HasSize), With<Door>>,
     mut events: EventReader<NewCustomerEvent>,
+    mut arrival_events: EventReader<PartyArrivalEvent>,
+    party_members: Query<(&Customer, &PartyMember)>,
     mut commands: Commands,
     map: Res<Map>,
-    texture: Res<CustomerTexture>,
+    textures: Res<CustomerTextures>,
     menu: Res<Menu>,
+    calendar: Res<Calendar>,
+    reputation: Res<Reputation>,
     mut personalities: ResMut<Personalities>,
 ) {
     let mut rng = rand::thread_rng();
     // FIXME: assume customers are all 1x1 entities.
     let size = MapSize { width: 1, height: 1 };
-    for _event in events.iter() {
+    let roster_size = personalities.data.len();
+    let mut arrivals = events.iter().map(|_| (None, true)).collect::<Vec<_>>();
+    arrivals.extend(arrival_events.iter().map(|event| (Some(event.0), false)));
+
+    for (party, leader) in arrivals {
+        let companions = party_members
+            .iter()
+            .filter(|(_, member)| Some(member.party) == party)
+            .collect::<Vec<_>>();
         let (transform, sized) = doors.iter().next().unwrap();
         let door_pos = transform_to_map_pos(&transform, &map, &sized.size);
         let screen_rect = map_to_screen(&door_pos, &size, &map);
@@ -340,25 +1005,71 @@ fn spawn_customer_by_door(
         let sized = HasSize { size };
         let transform = Transform::from_translation(translate);
 
+        let weights = personalities.data
+            .iter()
+            .filter(|(id, _)| companions.iter().all(|(customer, _)| customer.personality != **id))
+            .map(|(id, data)| {
+                (id.clone(), reputation.visit_weight(data.spawn_weight, data.affection.status()))
+            })
+            .collect::<Vec<_>>();
+        let personality = match weights.choose_weighted(&mut rng, |(_, weight)| *weight) {
+            Ok((id, _)) => id.clone(),
+            // Nobody wants to visit a teahouse with a reputation this bad.
+            Err(WeightedError::AllWeightsZero) => continue,
+            Err(_) => {
+                warn!("No personalities available to visit the teahouse");
+                continue;
+            }
+        };
         let sprite = SpriteSheetBundle {
-            texture_atlas: texture.0.clone(),
+            texture_atlas: textures.for_personality(&personality),
             transform,
             ..default()
         };
 
-        let personality = Personality::generate_random();
         let personality_data = personalities.data.get_mut(&personality).unwrap();
         let affection = personality_data.affection.clone();
         personality_data.visits += 1;
+        personality_data.last_visit = Some(calendar.day);
+        let last_tea = personality_data.served.last().cloned();
+
+        // Some customers bring friends, who follow them in once there's a table.
+        let party = match party {
+            None => {
+                let party_size = rng.gen_range(MIN_PARTY_SIZE..=MAX_PARTY_SIZE).min(roster_size);
+                if party_size > 1 && rng.gen_bool(party_chance(affection.status())) {
+                    Some(commands.spawn(Party::new(party_size)).id())
+                } else {
+                    None
+                }
+            }
+            party => party,
+        };
+        let birthday = personality_data.is_regular() &&
+            personality_data.birthday == calendar.day_of_year();
 
-        commands.spawn((
+        let expected = if rng.gen_bool(RECOMMENDATION_CHANCE) {
+            None
+        } else {
+            choose_order(&menu, personality_data)
+        };
+        let ordered_by = companions
+            .iter()
+            .find(|(_, member)| member.leader)
+            .map(|(customer, _)| customer.personality.clone());
+
+        let mut customer = commands.spawn((
             Customer {
-                expected: menu.teas.iter().choose(&mut rng).cloned().unwrap(),
+                expected,
                 personality,
+                last_tea,
+                ordered_by,
+                birthday,
+                celebrated: false,
             },
             affection,
             Facing(FacingDirection::Down),
-            State(LookingForChair),
+            State(LookingForChair::new()),
             AnimationData {
                 current_animation: AnimationState::StandDown.into(),
                 facing_conversion,
@@ -371,73 +1082,245 @@ fn spawn_customer_by_door(
             sized,
             sprite,
         ));
+        if let Some(party) = party {
+            customer.insert(PartyMember {
+                party,
+                leader,
+            });
+        }
     }
 }
 
 fn customer_spawner(
     mut state: Local<SpawnerState>,
     mut customer_events: EventWriter<NewCustomerEvent>,
+    reputation: Res<Reputation>,
     time: Res<Time>,
 ) {
     state.customer_timer.tick(time.delta());
     if state.customer_timer.finished() {
-        state.customer_timer = create_customer_timer();
+        state.customer_timer = create_customer_timer(reputation.busyness());
         customer_events.send(NewCustomerEvent);
     }
 }
 
+// Customers let slip one of their tastes in conversation, favouring ones the
+// player doesn't know about yet.
+fn preference_line(
+    personality: &PersonalityId,
+    preferences: &mut PersonalityState,
+) -> Option<String> {
+    let mut rng = rand::thread_rng();
+    let all = preferences.likes.iter().chain(&preferences.dislikes);
+    let unknown = all
+        .clone()
+        .filter(|ingredient| {
+            !preferences.known_likes.contains(ingredient) &&
+                !preferences.known_dislikes.contains(ingredient)
+        })
+        .cloned()
+        .choose(&mut rng);
+    let ingredient = unknown.or_else(|| all.cloned().choose(&mut rng))?;
+    preferences.learn(ingredient);
+    Some(if preferences.likes.contains(&ingredient) {
+        format!("{}: Oh, and I do love anything with {:?} in it.", personality, ingredient)
+    } else {
+        format!("{}: Oh, and just so you know, I can't stand {:?}.", personality, ingredient)
+    })
+}
+
+// Pick something from the menu that the customer will enjoy, or None to ask
+// for a recommendation when everything contains something they dislike.
+fn choose_order(menu: &Menu, preferences: &PersonalityState) -> Option<TeaRecipe> {
+    let mut rng = rand::thread_rng();
+    let acceptable = menu.teas
+        .iter()
+        .filter(|recipe| recipe.ingredients.iter().all(|(i, _)| !preferences.dislikes.contains(i)))
+        .collect::<Vec<_>>();
+    acceptable
+        .choose_weighted(&mut rng, |recipe| {
+            1 + 2 * recipe.ingredients.iter().filter(|(i, _)| preferences.likes.contains(i)).count()
+        })
+        .ok()
+        .map(|recipe| (*recipe).clone())
+}
+
 fn interact_with_customers(
     mut player_interacted_events: EventReader<PlayerInteracted>,
-    mut transfer_events: EventWriter<TransferHeldEntity>,
-    mut customers: Query<(Entity, &Customer, &mut Affection)>,
-    mut teapot: Query<&mut TeaPot>,
+    mut customers: Query<(Entity, &mut Customer, &mut Affection, Option<&Holding>)>,
+    party_members: Query<(Entity, &PartyMember)>,
+    parties: Query<&Party>,
+    finding_seats: Query<(), Or<(With<State<LookingForChair>>, With<State<MovingToChair>>)>>,
+    mut players: Query<&mut Player>,
+    cups: Query<&Cup>,
+    mut rated_events: EventWriter<BrewRatedEvent>,
+    mut money: ResMut<Money>,
+    mut personalities: ResMut<Personalities>,
     asset_server: Res<AssetServer>,
     mut game_state: ResMut<bevy::prelude::State<GameState>>,
-    time: Res<Time>,
     mut commands: Commands,
 ) {
     for event in player_interacted_events.iter() {
-        let (customer_entity, customer, mut affection) = match customers.get_mut(event.interacted_entity) {
-            Ok(result) => result,
-            Err(_) => continue,
+        // A party's leader orders for everybody, once they have all sat down.
+        let party_order = match party_members.get(event.interacted_entity) {
+            Ok((_, membership)) if membership.leader => {
+                let companions = party_members
+                    .iter()
+                    .filter(|(entity, member)| {
+                        member.party == membership.party && *entity != event.interacted_entity
+                    })
+                    .map(|(entity, _)| entity)
+                    .collect::<Vec<_>>();
+                let size = parties.get(membership.party).map_or(1, |party| party.size);
+                if companions.len() + 1 < size ||
+                    companions.iter().any(|entity| finding_seats.contains(*entity))
+                {
+                    PartyOrder::Waiting
+                } else {
+                    PartyOrder::Ready(companions
+                        .into_iter()
+                        .filter_map(|entity| customers.get(entity).ok())
+                        .map(|(_, companion, ..)| {
+                            (companion.personality.clone(), companion.order())
+                        })
+                        .collect())
+                }
+            }
+            _ => PartyOrder::Solo,
         };
-        if let Some(held) = event.held_entity {
-            if let Ok(mut teapot) = teapot.get_mut(held) {
-                if teapot.steeped_at.is_some() {
-                    transfer_events.send(TransferHeldEntity {
-                        holder: event.player_entity,
-                        receiver: customer_entity,
-                    });
-                    //FIXME: wasm issues
-                    teapot.steeped_for = Some(time.last_update().unwrap() - teapot.steeped_at.unwrap());
-
-                    let (reaction, conversation) = customer.tea_delivery(&teapot);
-                    affection.react(reaction);
-                    game_state.set(GameState::Dialog).unwrap();
-                    show_message_box(customer_entity, &mut commands, conversation, &asset_server);
-                    return;
+        let (customer_entity, mut customer, mut affection, customer_holding) =
+            match customers.get_mut(event.interacted_entity) {
+                Ok(result) => result,
+                Err(_) => continue,
+            };
+        let preferences = personalities.data.get_mut(&customer.personality).unwrap();
+
+        // A teaspoon of something they love makes a fine birthday present.
+        if customer.birthday && !customer.celebrated && event.held_entity.is_none() {
+            let mut player = players.get_mut(event.player_entity).unwrap();
+            let gift = preferences.likes
+                .iter()
+                .find(|ingredient| player.carrying.contains_key(*ingredient))
+                .cloned();
+            if let Some(gift) = gift {
+                let units = player.carrying.get_mut(&gift).unwrap();
+                units.pop();
+                if units.is_empty() {
+                    player.carrying.remove(&gift);
                 }
+                customer.celebrated = true;
+                affection.react(Reaction::MajorPositive);
+                let conversation = vec![
+                    format!("You: Happy birthday! I saved you some {:?}.", gift),
+                    format!("{}: You remembered! Thank you so much!", customer.personality),
+                ];
+                game_state.set(GameState::Dialog).unwrap();
+                show_message_box(customer_entity, &mut commands, conversation, &asset_server);
+                return;
             }
         }
 
+        // Customers who have already been served just want to chat.
+        let held_cup = match (event.held_entity, customer_holding) {
+            (Some(held), None) => cups.get(held).ok().map(|cup| (held, cup)),
+            _ => None,
+        };
+        if let Some((cup_entity, Cup { tea: Some(serving) })) = held_cup {
+            let (reaction, mut conversation) = customer.tea_delivery(serving, preferences);
+            // Remembered or not, the birthday has been marked one way or another.
+            customer.celebrated = customer.birthday;
+            // Whatever they complained about, the player now knows.
+            for ingredient in serving.ingredients.keys() {
+                if preferences.dislikes.contains(ingredient) {
+                    preferences.learn(*ingredient);
+                }
+            }
+            let price = customer.expected
+                .as_ref()
+                .map_or(RECOMMENDATION_PRICE, |recipe| recipe.price());
+            let tip = tip(reaction, affection.status());
+            money.earn(price + tip);
+            conversation.push(if tip > 0 {
+                format!(
+                    "{} pays {} coins, and leaves a {} coin tip.",
+                    customer.personality,
+                    price,
+                    tip,
+                )
+            } else {
+                format!("{} pays {} coins.", customer.personality, price)
+            });
+            rated_events.send(BrewRatedEvent {
+                customer_entity,
+                customer: customer.personality.to_string(),
+                ingredients: serving.ingredients.clone(),
+                additions: serving.additions.clone(),
+                reaction,
+            });
+            preferences.served.push(ServedTea {
+                recipe: customer.expected.as_ref().map(|recipe| recipe.name.clone()),
+                reaction,
+            });
+            commands.entity(event.player_entity).remove::<Holding>();
+            commands.entity(customer_entity)
+                .add_child(cup_entity)
+                .insert(Holding {
+                    entity: cup_entity,
+                });
+            affection.react(reaction);
+            game_state.set(GameState::Dialog).unwrap();
+            show_message_box(customer_entity, &mut commands, conversation, &asset_server);
+            return;
+        }
+
+        let mut conversation = customer.conversation(preferences, &party_order);
+        if preferences.is_regular() && !preferences.known_birthday && !customer.birthday {
+            preferences.known_birthday = true;
+            conversation.push(format!(
+                "{}: My birthday is on day {} of the year, if you were wondering.",
+                customer.personality,
+                preferences.birthday + 1,
+            ));
+        }
+        if let Some(line) = preference_line(&customer.personality, preferences) {
+            conversation.push(line);
+        }
         game_state.set(GameState::Dialog).unwrap();
-        show_message_box(customer_entity, &mut commands, customer.conversation(), &asset_server);
+        show_message_box(customer_entity, &mut commands, conversation, &asset_server);
         return;
     }
 }
 
 #[derive(Resource)]
-struct CustomerTexture(Handle<TextureAtlas>);
+struct CustomerTextures {
+    // The sheet used by anybody who doesn't have one of their own.
+    default: Handle<TextureAtlas>,
+    personalities: HashMap<PersonalityId, (Handle<Image>, Handle<TextureAtlas>)>,
+    // Sheets that haven't finished loading yet.
+    loading: Vec<PersonalityId>,
+}
 
-fn init_texture(
-    asset_server: Res<AssetServer>,
-    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
-    mut animation_data: ResMut<AtlasAnimationData>,
-    mut commands: Commands,
-) {
-    let texture_handle = asset_server.load("woman.png");
-    let texture_atlas =
-        TextureAtlas::from_grid(texture_handle, Vec2::new(TILE_SIZE, TILE_SIZE), 4, 4, None, None);
+impl CustomerTextures {
+    fn for_personality(&self, id: &PersonalityId) -> Handle<TextureAtlas> {
+        match self.personalities.get(id) {
+            Some((_, atlas)) => atlas.clone(),
+            None => self.default.clone(),
+        }
+    }
+}
+
+// Every customer sheet shares the same layout: a row of walking frames for
+// each direction.
+fn load_customer_sheet(
+    path: &str,
+    asset_server: &AssetServer,
+    texture_atlases: &mut Assets<TextureAtlas>,
+    animation_data: &mut AtlasAnimationData,
+) -> (Handle<Image>, Handle<TextureAtlas>) {
+    let texture_handle = asset_server.load(path);
+    let texture_atlas = TextureAtlas::from_grid(
+        texture_handle.clone(), Vec2::new(TILE_SIZE, TILE_SIZE), 4, 4, None, None,
+    );
     let texture_atlas_handle = texture_atlases.add(texture_atlas);
     animation_data.data.insert(
         texture_atlas_handle.clone(),
@@ -452,7 +1335,67 @@ fn init_texture(
             AnimData { index: 12, frames: 1, delay: 1., },
         ],
     );
-    commands.insert_resource(CustomerTexture(texture_atlas_handle));
+    (texture_handle, texture_atlas_handle)
+}
+
+fn init_texture(
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut animation_data: ResMut<AtlasAnimationData>,
+    mut commands: Commands,
+) {
+    let (_, default_atlas) = load_customer_sheet(
+        DEFAULT_SPRITE, &asset_server, &mut texture_atlases, &mut animation_data,
+    );
+    commands.insert_resource(CustomerTextures {
+        default: default_atlas,
+        personalities: HashMap::new(),
+        loading: vec![],
+    });
+}
+
+fn load_personality_textures(
+    personalities: Res<Personalities>,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut animation_data: ResMut<AtlasAnimationData>,
+    mut textures: ResMut<CustomerTextures>,
+) {
+    if personalities.is_changed() {
+        for (id, state) in &personalities.data {
+            if state.sprite == DEFAULT_SPRITE || textures.personalities.contains_key(id) {
+                continue;
+            }
+            let sheet = load_customer_sheet(
+                &state.sprite, &asset_server, &mut texture_atlases, &mut animation_data,
+            );
+            textures.personalities.insert(id.clone(), sheet);
+            textures.loading.push(id.clone());
+        }
+    }
+    if textures.loading.is_empty() {
+        return;
+    }
+
+    // A sheet that fails to load is replaced with the default one, so that any
+    // customers already wearing it stay visible.
+    let fallback = texture_atlases.get(&textures.default).cloned();
+    let textures = &mut *textures;
+    let sheets = &textures.personalities;
+    textures.loading.retain(|id| {
+        let (image, atlas) = &sheets[id];
+        match asset_server.get_load_state(image) {
+            LoadState::Failed => {
+                warn!("Unable to load sprite sheet for {}", id);
+                if let (Some(sheet), Some(fallback)) = (texture_atlases.get_mut(atlas), &fallback) {
+                    *sheet = fallback.clone();
+                }
+                false
+            }
+            LoadState::Loaded => false,
+            _ => true,
+        }
+    });
 }
 
 fn standing_conversion(facing: FacingDirection) -> AnimationState {
@@ -472,3 +1415,90 @@ fn facing_conversion(facing: FacingDirection) -> usize {
         FacingDirection::Left => AnimationState::WalkLeft,
     }.into()
 }
+
+#[test]
+fn orders_avoid_disliked_ingredients() {
+    use crate::tea::Ingredient;
+
+    let recipe = |ingredient: Ingredient| TeaRecipe {
+        ingredients: vec![(ingredient, 2)],
+        name: format!("{:?}", ingredient),
+    };
+    let preferences = PersonalityState {
+        dislikes: vec![Ingredient::Lemon],
+        ..default()
+    };
+    let menu = Menu {
+        teas: vec![recipe(Ingredient::Lemon), recipe(Ingredient::BlackTea)],
+    };
+    for _ in 0..10 {
+        assert_eq!(choose_order(&menu, &preferences).unwrap().name, "BlackTea");
+    }
+
+    let menu = Menu {
+        teas: vec![recipe(Ingredient::Lemon)],
+    };
+    assert!(choose_order(&menu, &preferences).is_none());
+}
+
+#[test]
+fn conversation_reflects_familiarity() {
+    let customer = Customer {
+        expected: None,
+        personality: PersonalityId("Frieda".to_owned()),
+        last_tea: None,
+        ordered_by: None,
+        birthday: false,
+        celebrated: false,
+    };
+    let first_visit = PersonalityState {
+        visits: 1,
+        ..default()
+    };
+    let conversation = customer.conversation(&first_visit, &PartyOrder::Solo);
+    assert_eq!(conversation[0], "You: Welcome to Sereni Tea!");
+
+    let customer = Customer {
+        last_tea: Some(ServedTea {
+            recipe: Some("Mad Chai".to_owned()),
+            reaction: Reaction::MajorPositive,
+        }),
+        ..customer
+    };
+    let tenth_visit = PersonalityState {
+        visits: 10,
+        affection: RelationshipStatus::Crushing.into(),
+        ..default()
+    };
+    let conversation = customer.conversation(&tenth_visit, &PartyOrder::Solo);
+    assert_ne!(conversation[0], "You: Welcome to Sereni Tea!");
+    assert!(conversation.iter().any(|line| line.contains("the Mad Chai")));
+}
+
+#[test]
+fn party_leader_orders_for_everyone() {
+    let state = PersonalityState::default();
+    let leader = Customer {
+        expected: None,
+        personality: PersonalityId("Frieda".to_owned()),
+        last_tea: None,
+        ordered_by: None,
+        birthday: false,
+        celebrated: false,
+    };
+    let conversation = leader.conversation(&state, &PartyOrder::Waiting);
+    assert!(!conversation.contains(&"You: Coming right up!.".to_owned()));
+
+    let companions = vec![(PersonalityId("Lucien".to_owned()), "the Mad Chai".to_owned())];
+    let conversation = leader.conversation(&state, &PartyOrder::Ready(companions));
+    assert!(conversation.contains(&"Frieda: And Lucien would like the Mad Chai.".to_owned()));
+
+    let companion = Customer {
+        personality: PersonalityId("Lucien".to_owned()),
+        ordered_by: Some(PersonalityId("Frieda".to_owned())),
+        ..leader
+    };
+    let conversation = companion.conversation(&state, &PartyOrder::Solo);
+    assert!(conversation.contains(&"Lucien: Frieda is ordering for all of us.".to_owned()));
+    assert!(!conversation.contains(&"You: Coming right up!.".to_owned()));
+}
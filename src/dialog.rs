@@ -28,15 +28,39 @@ pub struct Conversation {
     box_entity: Entity,
 }
 
-pub fn show_message_box(
-    entity: Entity,
+// A box of text along the bottom of the screen, with a reminder of which keys
+// to press underneath.
+pub struct Panel {
+    pub text: String,
+    pub hint: &'static str,
+    pub height: f32,
+    pub font_size: f32,
+    pub background: Color,
+    pub hint_color: Color,
+}
+
+impl Default for Panel {
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            hint: "",
+            height: 200.0,
+            font_size: 25.0,
+            background: Color::rgb(0.4, 0.4, 1.0),
+            hint_color: Color::rgb(0.8, 0.8, 1.0),
+        }
+    }
+}
+
+// Returns the entity to despawn once the panel is closed. The text that changes
+// is tagged with the given marker.
+pub fn spawn_panel(
     commands: &mut Commands,
-    messages: Vec<String>,
     asset_server: &AssetServer,
-) {
-    commands.entity(entity).insert(Paused);
-
-    let id = commands
+    marker: impl Component,
+    panel: Panel,
+) -> Entity {
+    commands
         .spawn(NodeBundle {
             style: Style {
                 size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
@@ -49,7 +73,7 @@ pub fn show_message_box(
             parent
                 .spawn(NodeBundle {
                     style: Style {
-                        size: Size::new(Val::Px(800.0), Val::Px(200.0)),
+                        size: Size::new(Val::Px(800.0), Val::Px(panel.height)),
                         position_type: PositionType::Absolute,
                         position: UiRect {
                             left: Val::Px(210.0),
@@ -59,34 +83,49 @@ pub fn show_message_box(
                         border: UiRect::all(Val::Px(20.0)),
                         ..default()
                     },
-                    background_color: Color::rgb(0.4, 0.4, 1.0).into(),
+                    background_color: panel.background.into(),
                     ..default()
                 })
                 .with_children(|parent| {
                     parent.spawn((
-                        MessageBox,
+                        marker,
                         TextBundle::from_sections([
                             TextSection::new(
-                                &messages[0],
+                                panel.text,
                                 TextStyle {
                                     font: asset_server.load("Lato-Medium.ttf"),
-                                    font_size: 25.0,
+                                    font_size: panel.font_size,
                                     color: Color::WHITE,
                                 },
                             ),
                             TextSection::new(
-                                "\n\nPress space...",
+                                format!("\n\n{}", panel.hint),
                                 TextStyle {
                                     font: asset_server.load("Lato-Medium.ttf"),
                                     font_size: 15.0,
-                                    color: Color::rgb(0.8, 0.8, 1.0),
+                                    color: panel.hint_color,
                                 },
                             ),
                         ]),
                     ));
                 });
         })
-        .id();
+        .id()
+}
+
+pub fn show_message_box(
+    entity: Entity,
+    commands: &mut Commands,
+    messages: Vec<String>,
+    asset_server: &AssetServer,
+) {
+    commands.entity(entity).insert(Paused);
+
+    let id = spawn_panel(commands, asset_server, MessageBox, Panel {
+        text: messages[0].clone(),
+        hint: "Press space...",
+        ..default()
+    });
 
     commands.spawn(Conversation {
         messages,
@@ -9,7 +9,10 @@ use crate::movable::Movable;
 use crate::menu::{StartingIngredients, spawn_menu};
 use crate::player::SpawnPlayerEvent;
 use crate::stair::spawn_staircase;
-use crate::tea::{SpawnTeapotEvent, spawn_cupboard, spawn_kettle, spawn_teastash, spawn_sink};
+use crate::supplier::{spawn_empty_shelf, spawn_order_board};
+use crate::tea::{
+    SpawnTeapotEvent, spawn_cupboard, spawn_cup_shelf, spawn_kettle, spawn_teastash, spawn_sink,
+};
 use rand_derive2::RandGen;
 use std::default::Default;
 use tiled::{LayerType, PropertyValue, ObjectShape};
@@ -63,7 +66,7 @@ pub struct Affection {
     affection: f32,
 }
 
-#[allow(dead_code)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub enum Reaction {
     Positive,
     MajorPositive,
@@ -71,7 +74,18 @@ pub enum Reaction {
     MajorNegative,
 }
 
-#[derive(RandGen, PartialEq, Copy, Clone)]
+impl Reaction {
+    /// The next less favourable reaction.
+    pub fn worsen(self) -> Reaction {
+        match self {
+            Reaction::MajorPositive => Reaction::Positive,
+            Reaction::Positive => Reaction::Negative,
+            Reaction::Negative | Reaction::MajorNegative => Reaction::MajorNegative,
+        }
+    }
+}
+
+#[derive(RandGen, PartialEq, PartialOrd, Copy, Clone, Debug)]
 pub enum RelationshipStatus {
     Angry,
     Neutral,
@@ -117,6 +131,13 @@ impl Affection {
         };
     }
 
+    /// Fade towards indifference, without ever turning into anger.
+    pub fn decay(&mut self, amount: f32) {
+        if self.affection > 0. {
+            self.affection = (self.affection - amount).max(0.);
+        }
+    }
+
     pub fn status(&self) -> RelationshipStatus {
         for (status, lower, upper) in AFFECTION_LEVELS {
             if (lower.is_infinite() || self.affection >= *lower) &&
@@ -146,8 +167,45 @@ pub struct Stove;
 #[derive(Component)]
 pub struct Door;
 
-#[derive(Component)]
-pub struct Chair;
+#[derive(Component, Default)]
+pub struct Chair {
+    // The customer who has claimed this seat, if any.
+    pub occupant: Option<Entity>,
+    // The table this chair is pulled up to, numbered in map order.
+    pub table: Option<usize>,
+}
+
+fn is_adjacent(a: &MapPos, b: &MapPos) -> bool {
+    a.x.abs_diff(b.x) + a.y.abs_diff(b.y) == 1
+}
+
+// Number each table on the map, where a table is any group of touching tiles
+// in the Tables layer.
+fn label_tables(tiles: &[MapPos]) -> Vec<usize> {
+    let mut labels: Vec<Option<usize>> = vec![None; tiles.len()];
+    let mut next_label = 0;
+    for start in 0..tiles.len() {
+        if labels[start].is_some() {
+            continue;
+        }
+        labels[start] = Some(next_label);
+        let mut pending = vec![start];
+        while let Some(current) = pending.pop() {
+            for (index, tile) in tiles.iter().enumerate() {
+                if labels[index].is_none() && is_adjacent(&tiles[current], tile) {
+                    labels[index] = Some(next_label);
+                    pending.push(index);
+                }
+            }
+        }
+        next_label += 1;
+    }
+    labels.into_iter().map(Option::unwrap).collect()
+}
+
+fn table_for_chair(tiles: &[MapPos], labels: &[usize], chair: &MapPos) -> Option<usize> {
+    tiles.iter().position(|tile| is_adjacent(tile, chair)).map(|index| labels[index])
+}
 
 const STARTING_INGREDIENT_AMOUNT: u32 = 50;
 
@@ -184,6 +242,9 @@ pub fn setup2(
     };
 
     let mut stashes_spawned = 0;
+    // Chairs are spawned once every table on the map is known.
+    let mut chairs = vec![];
+    let mut table_tiles = vec![];
 
     let map = assets.get(&map2.handle).unwrap();
     let map = &map.map;
@@ -194,6 +255,7 @@ pub fn setup2(
     for layer in map.layers() {
         let properties = &layer.properties;
         let solid = properties.get("solid").map_or(false, |value| *value == PropertyValue::BoolValue(true));
+        let tables = layer.name == "Tables";
         println!("{:?}", layer.name);
         match layer.layer_type() {
             LayerType::TileLayer(layer) => {
@@ -204,6 +266,9 @@ pub fn setup2(
                             None => continue,
                         };
                         let pos = MapPos { x: x as usize, y: y as usize };
+                        if tables {
+                            table_tiles.push(pos);
+                        }
                         let size = MapSize { width: 1, height: 1 };
                         let rect = map_to_screen(&pos, &size, &map2);
                         let pos = Vec2::new(rect.x, rect.y);
@@ -279,6 +344,18 @@ pub fn setup2(
                             );
                             stashes_spawned += 1;
                         }
+                        "empty_shelf" => {
+                            spawn_empty_shelf(
+                                &mut commands,
+                                movable,
+                                sized,
+                                transform,
+                                textures.interior_atlas.clone(),
+                            );
+                        }
+                        "order_board" => {
+                            spawn_order_board(&mut commands, movable, sized, transform);
+                        }
                         "sink" => {
                             spawn_sink(&mut commands, movable, sized, transform);
                         }
@@ -292,7 +369,7 @@ pub fn setup2(
                             cat_spawner.send(SpawnCatEvent(pos));
                         }
                         "chair" => {
-                            commands.spawn((Chair, sized, transform));
+                            chairs.push((pos, sized, transform));
                         }
                         "cupboard" => {
                             spawn_cupboard(
@@ -302,6 +379,9 @@ pub fn setup2(
                                 transform,
                             );
                         }
+                        "cup_shelf" => {
+                            spawn_cup_shelf(&mut commands, movable, sized, transform);
+                        }
                         "menu" => {
                             spawn_menu(&mut commands, movable, sized, transform);
                         }
@@ -337,6 +417,15 @@ pub fn setup2(
         z += 0.1;
     }
 
+    let labels = label_tables(&table_tiles);
+    for (pos, sized, transform) in chairs {
+        let chair = Chair {
+            table: table_for_chair(&table_tiles, &labels, &pos),
+            ..default()
+        };
+        commands.spawn((chair, sized, transform));
+    }
+
     commands.insert_resource(textures);
 }
 
@@ -345,3 +434,15 @@ pub fn start_game(
 ) {
     game_state.set(GameState::InGame).unwrap();
 }
+
+#[test]
+fn chairs_are_grouped_by_table() {
+    let pos = |x, y| MapPos { x, y };
+    let tiles = [pos(2, 2), pos(3, 2), pos(3, 3), pos(6, 2)];
+    let labels = label_tables(&tiles);
+    assert_eq!(labels, vec![0, 0, 0, 1]);
+    assert_eq!(table_for_chair(&tiles, &labels, &pos(1, 2)), Some(0));
+    assert_eq!(table_for_chair(&tiles, &labels, &pos(3, 4)), Some(0));
+    assert_eq!(table_for_chair(&tiles, &labels, &pos(7, 2)), Some(1));
+    assert_eq!(table_for_chair(&tiles, &labels, &pos(5, 5)), None);
+}
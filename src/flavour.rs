@@ -0,0 +1,124 @@
+use std::ops::{Add, AddAssign};
+use strum::{EnumIter, IntoEnumIterator};
+
+#[derive(EnumIter, Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Taste {
+    Sweet,
+    Bitter,
+    Floral,
+    Citrus,
+    Creamy,
+    Earthy,
+}
+
+impl Taste {
+    pub fn adjective(&self) -> &'static str {
+        match self {
+            Taste::Sweet => "sweet",
+            Taste::Bitter => "bitter",
+            Taste::Floral => "floral",
+            Taste::Citrus => "zesty",
+            Taste::Creamy => "creamy",
+            Taste::Earthy => "earthy",
+        }
+    }
+}
+
+#[derive(Default, Copy, Clone, PartialEq, Debug)]
+pub struct Flavour {
+    pub sweet: f32,
+    pub bitter: f32,
+    pub floral: f32,
+    pub citrus: f32,
+    pub creamy: f32,
+    pub earthy: f32,
+}
+
+impl Flavour {
+    pub const fn new(
+        sweet: f32,
+        bitter: f32,
+        floral: f32,
+        citrus: f32,
+        creamy: f32,
+        earthy: f32,
+    ) -> Flavour {
+        Flavour { sweet, bitter, floral, citrus, creamy, earthy }
+    }
+
+    pub fn get(&self, taste: Taste) -> f32 {
+        match taste {
+            Taste::Sweet => self.sweet,
+            Taste::Bitter => self.bitter,
+            Taste::Floral => self.floral,
+            Taste::Citrus => self.citrus,
+            Taste::Creamy => self.creamy,
+            Taste::Earthy => self.earthy,
+        }
+    }
+
+    pub fn scaled(&self, factor: f32) -> Flavour {
+        Flavour {
+            sweet: self.sweet * factor,
+            bitter: self.bitter * factor,
+            floral: self.floral * factor,
+            citrus: self.citrus * factor,
+            creamy: self.creamy * factor,
+            earthy: self.earthy * factor,
+        }
+    }
+
+    /// The tastes present in this flavour, strongest first.
+    pub fn dominant(&self) -> Vec<Taste> {
+        let mut tastes = Taste::iter()
+            .filter(|taste| self.get(*taste) > 0.)
+            .collect::<Vec<_>>();
+        tastes.sort_by(|a, b| self.get(*b).partial_cmp(&self.get(*a)).unwrap());
+        tastes
+    }
+
+    pub fn describe(&self) -> String {
+        let tastes = self.dominant();
+        let primary = match tastes.first() {
+            Some(taste) => *taste,
+            None => return "It doesn't taste of much.".to_owned(),
+        };
+        match tastes.get(1) {
+            Some(secondary) if self.get(*secondary) >= self.get(primary) / 2. => format!(
+                "It's wonderfully {}, with a {} note.",
+                primary.adjective(),
+                secondary.adjective(),
+            ),
+            _ => format!("It's very {}.", primary.adjective()),
+        }
+    }
+}
+
+impl Add for Flavour {
+    type Output = Flavour;
+
+    fn add(self, other: Flavour) -> Flavour {
+        Flavour {
+            sweet: self.sweet + other.sweet,
+            bitter: self.bitter + other.bitter,
+            floral: self.floral + other.floral,
+            citrus: self.citrus + other.citrus,
+            creamy: self.creamy + other.creamy,
+            earthy: self.earthy + other.earthy,
+        }
+    }
+}
+
+impl AddAssign for Flavour {
+    fn add_assign(&mut self, other: Flavour) {
+        *self = *self + other;
+    }
+}
+
+#[test]
+fn dominant_tastes() {
+    let flavour = Flavour::new(0.2, 0., 0.5, 0., 0.9, 0.);
+    assert_eq!(flavour.dominant(), vec![Taste::Creamy, Taste::Floral, Taste::Sweet]);
+    assert_eq!(flavour.describe(), "It's wonderfully creamy, with a floral note.");
+    assert_eq!(Flavour::default().describe(), "It doesn't taste of much.");
+}
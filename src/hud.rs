@@ -0,0 +1,195 @@
+use bevy::prelude::*;
+use crate::calendar::Calendar;
+use crate::money::Money;
+use crate::player::Player;
+use crate::reputation::Reputation;
+use crate::tea::Freshness;
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_startup_system(setup)
+            .add_system(update_carrying_readout)
+            .add_system(update_money_readout)
+            .add_system(update_calendar_readout)
+            .add_system(update_reputation_readout);
+    }
+}
+
+#[derive(Component)]
+struct CarryingReadout;
+
+#[derive(Component)]
+struct MoneyReadout;
+
+#[derive(Component)]
+struct CalendarReadout;
+
+#[derive(Component)]
+struct ReputationReadout;
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    commands.spawn((
+        CarryingReadout,
+        TextBundle::from_section(
+            describe_carrying(&Player::default()),
+            TextStyle {
+                font: asset_server.load("Lato-Medium.ttf"),
+                font_size: 20.0,
+                color: Color::WHITE,
+            },
+        )
+            .with_text_alignment(TextAlignment::TOP_LEFT)
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(5.0),
+                    left: Val::Px(15.0),
+                    ..default()
+                },
+                ..default()
+            }),
+    ));
+
+    commands.spawn((
+        MoneyReadout,
+        TextBundle::from_section(
+            describe_money(&Money::default()),
+            TextStyle {
+                font: asset_server.load("Lato-Medium.ttf"),
+                font_size: 20.0,
+                color: Color::rgb(1.0, 0.9, 0.5),
+            },
+        )
+            .with_text_alignment(TextAlignment::TOP_RIGHT)
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(5.0),
+                    right: Val::Px(15.0),
+                    ..default()
+                },
+                ..default()
+            }),
+    ));
+
+    commands.spawn((
+        CalendarReadout,
+        TextBundle::from_section(
+            Calendar::default().describe(),
+            TextStyle {
+                font: asset_server.load("Lato-Medium.ttf"),
+                font_size: 20.0,
+                color: Color::WHITE,
+            },
+        )
+            .with_text_alignment(TextAlignment::TOP_RIGHT)
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(30.0),
+                    right: Val::Px(15.0),
+                    ..default()
+                },
+                ..default()
+            }),
+    ));
+
+    commands.spawn((
+        ReputationReadout,
+        TextBundle::from_section(
+            describe_reputation(&Reputation::default()),
+            TextStyle {
+                font: asset_server.load("Lato-Medium.ttf"),
+                font_size: 20.0,
+                color: Color::WHITE,
+            },
+        )
+            .with_text_alignment(TextAlignment::TOP_RIGHT)
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(55.0),
+                    right: Val::Px(15.0),
+                    ..default()
+                },
+                ..default()
+            }),
+    ));
+}
+
+fn describe_carrying(player: &Player) -> String {
+    if player.carrying.is_empty() {
+        return "Carrying: nothing".to_owned();
+    }
+    let mut ingredients = player.carrying
+        .iter()
+        .map(|(ingredient, units)| {
+            let stalest = units.iter().fold(1., |stalest: f32, freshness| stalest.min(*freshness));
+            match Freshness::from_value(stalest) {
+                Freshness::Fresh => format!("{} tsp {:?}", units.len(), ingredient),
+                freshness => format!("{} tsp {:?} ({})", units.len(), ingredient, freshness.adjective()),
+            }
+        })
+        .collect::<Vec<_>>();
+    ingredients.sort();
+    format!("Carrying: {}", ingredients.join(", "))
+}
+
+fn update_carrying_readout(
+    player: Query<&Player, Changed<Player>>,
+    mut readout: Query<&mut Text, With<CarryingReadout>>,
+) {
+    if player.is_empty() {
+        return;
+    }
+    let player = player.single();
+    let mut text = readout.single_mut();
+    text.sections[0].value = describe_carrying(player);
+}
+
+fn describe_money(money: &Money) -> String {
+    format!("Coins: {} (earned {})", money.coins, money.earned)
+}
+
+fn update_money_readout(
+    money: Res<Money>,
+    mut readout: Query<&mut Text, With<MoneyReadout>>,
+) {
+    if !money.is_changed() {
+        return;
+    }
+    let mut text = readout.single_mut();
+    text.sections[0].value = describe_money(&money);
+}
+
+fn update_calendar_readout(
+    calendar: Res<Calendar>,
+    mut readout: Query<&mut Text, With<CalendarReadout>>,
+) {
+    if !calendar.is_changed() {
+        return;
+    }
+    let mut text = readout.single_mut();
+    text.sections[0].value = calendar.describe();
+}
+
+fn describe_reputation(reputation: &Reputation) -> String {
+    format!("Reputation: {}", reputation.describe())
+}
+
+fn update_reputation_readout(
+    reputation: Res<Reputation>,
+    mut readout: Query<&mut Text, With<ReputationReadout>>,
+) {
+    if !reputation.is_changed() {
+        return;
+    }
+    let mut text = readout.single_mut();
+    text.sections[0].value = describe_reputation(&reputation);
+}
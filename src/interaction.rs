@@ -16,15 +16,14 @@ impl Plugin for InteractionPlugin {
             .add_system(pick_up_item)
             .add_system(mirror_carried_item)
             .add_system(auto_pick_up_item)
-            .add_system(transfer)
             .add_system(drop)
             .add_event::<PlayerInteracted>()
-            .add_event::<TransferHeldEntity>()
             .add_event::<DropHeldEntity>()
             .add_system_set(
                 SystemSet::on_update(GameState::InGame)
                     .with_system(keyboard_input)
                     .with_system(highlight_interactable)
+                    .with_system(refresh_interactable_message)
             );
     }
 }
@@ -115,17 +114,35 @@ fn highlight_interactable(
                     DEFAULT_EXPIRY,
                 ));
                 }
+                interactable.colliding = true;
             }
-            interactable.colliding = true;
             found = true;
         } else if !collision || found {
-            interactable.colliding = false;
+            if interactable.colliding {
+                interactable.colliding = false;
+            }
 
             status_events.send(StatusEvent::clear(entity));
         }
     }
 }
 
+// Keep the message up to date for whatever the player is standing next to.
+fn refresh_interactable_message(
+    interactables: Query<(Entity, &Interactable), Changed<Interactable>>,
+    mut status_events: EventWriter<StatusEvent>,
+) {
+    for (entity, interactable) in &interactables {
+        if interactable.colliding && !interactable.message.is_empty() {
+            status_events.send(StatusEvent::timed_message(
+                entity,
+                interactable.message.clone(),
+                DEFAULT_EXPIRY,
+            ));
+        }
+    }
+}
+
 pub struct PlayerInteracted {
     pub player_entity: Entity,
     pub interacted_entity: Entity,
@@ -288,25 +305,9 @@ fn drop(
     }
 }
 
-pub struct TransferHeldEntity {
-    pub holder: Entity,
-    pub receiver: Entity,
-}
-
-fn transfer(
-    mut events: EventReader<TransferHeldEntity>,
-    holder: Query<&Holding>,
-    mut commands: Commands,
-) {
-    for event in events.iter() {
-        let held_entity = match holder.get(event.holder) {
-            Ok(holder) => holder.entity,
-            Err(_) => continue,
-        };
-        commands.entity(event.receiver).add_child(held_entity);
-        commands.entity(event.receiver).insert(Holding {
-            entity: held_entity,
-        });
-        commands.entity(event.holder).remove::<Holding>();
-    }
+// Destroy an entity that is currently being held, ensuring that the holder
+// no longer refers to it.
+pub fn despawn_held_entity(commands: &mut Commands, holder: Entity, held: Entity) {
+    commands.entity(holder).remove::<Holding>();
+    commands.entity(held).despawn_recursive();
 }
@@ -0,0 +1,168 @@
+use bevy::prelude::*;
+use crate::GameState;
+use crate::customer::Customer;
+use crate::dialog::{Panel, spawn_panel};
+use crate::entity::{Paused, Reaction};
+use crate::personality::{PersonalityId, Personalities, State as PersonalityState};
+use crate::tea::Ingredient;
+
+pub struct JournalPlugin;
+
+impl Plugin for JournalPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(open_journal)
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Journal)
+                    .with_system(run_journal)
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Journal)
+                    .with_system(exit_journal)
+            );
+    }
+}
+
+// Only the most recent teas fit on a page.
+const SERVED_TEAS_SHOWN: usize = 5;
+
+#[derive(Component)]
+struct JournalText;
+
+#[derive(Component)]
+struct JournalPage {
+    index: usize,
+    box_entity: Entity,
+}
+
+fn open_journal(
+    keys: Res<Input<KeyCode>>,
+    customers: Query<Entity, With<Customer>>,
+    mut game_state: ResMut<State<GameState>>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    if !keys.just_released(KeyCode::J) {
+        return;
+    }
+    game_state.set(GameState::Journal).unwrap();
+
+    // Nobody loses patience while the player is reading.
+    for customer in &customers {
+        commands.entity(customer).insert(Paused);
+    }
+
+    let id = spawn_panel(&mut commands, &asset_server, JournalText, Panel {
+        hint: "Left/right to turn the page, backspace to close.",
+        height: 380.0,
+        font_size: 20.0,
+        background: Color::rgb(0.3, 0.4, 0.3),
+        hint_color: Color::rgb(0.85, 1.0, 0.85),
+        ..default()
+    });
+
+    commands.spawn(JournalPage {
+        index: 0,
+        box_entity: id,
+    });
+}
+
+fn describe_reaction(reaction: Reaction) -> &'static str {
+    match reaction {
+        Reaction::MajorPositive => "loved it",
+        Reaction::Positive => "enjoyed it",
+        Reaction::Negative => "didn't enjoy it",
+        Reaction::MajorNegative => "hated it",
+    }
+}
+
+fn describe_preferences(known: &[Ingredient]) -> String {
+    if known.is_empty() {
+        return "unknown".to_owned();
+    }
+    known
+        .iter()
+        .map(|ingredient| format!("{:?}", ingredient))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn describe_personality(id: &PersonalityId, state: &PersonalityState) -> String {
+    let birthday = if state.known_birthday {
+        format!("day {}", state.birthday + 1)
+    } else {
+        "unknown".to_owned()
+    };
+    let mut description = format!(
+        "{}\nRelationship: {:?}\nVisits: {}\nBirthday: {}\nLikes: {}\nDislikes: {}\n\nTeas served:",
+        id,
+        state.affection.status(),
+        state.visits,
+        birthday,
+        describe_preferences(&state.known_likes),
+        describe_preferences(&state.known_dislikes),
+    );
+    if state.served.is_empty() {
+        description += "\nnone yet";
+    }
+    let skipped = state.served.len().saturating_sub(SERVED_TEAS_SHOWN);
+    for tea in &state.served[skipped..] {
+        description += &format!("\n{}: {}", tea.describe(), describe_reaction(tea.reaction));
+    }
+    description
+}
+
+fn run_journal(
+    mut page: Query<&mut JournalPage>,
+    mut text_box: Query<&mut Text, With<JournalText>>,
+    personalities: Res<Personalities>,
+    keys: Res<Input<KeyCode>>,
+    mut game_state: ResMut<State<GameState>>,
+) {
+    let mut page = page.single_mut();
+    let mut text_box = text_box.single_mut();
+
+    let mut ids = personalities.data.keys().collect::<Vec<_>>();
+    ids.sort_by(|a, b| a.0.cmp(&b.0));
+    let pages = ids.len();
+
+    if pages > 0 {
+        if keys.just_released(KeyCode::Left) {
+            page.index = (page.index + pages - 1) % pages;
+        }
+        if keys.just_released(KeyCode::Right) {
+            page.index = (page.index + 1) % pages;
+        }
+    }
+
+    text_box.sections[0].value = match ids.get(page.index) {
+        Some(id) => format!(
+            "Journal ({}/{})\n\n{}",
+            page.index + 1,
+            pages,
+            describe_personality(id, &personalities.data[*id]),
+        ),
+        None => "Your journal is empty.".to_owned(),
+    };
+
+    if keys.just_released(KeyCode::Back) {
+        game_state.set(GameState::InGame).unwrap();
+    }
+}
+
+fn exit_journal(
+    page: Query<(Entity, &JournalPage)>,
+    paused: Query<Entity, With<Paused>>,
+    mut commands: Commands,
+) {
+    let (entity, page) = page.single();
+    commands.entity(page.box_entity).despawn_recursive();
+    commands.entity(entity).despawn();
+
+    for paused_entity in &paused {
+        commands.entity(paused_entity).remove::<Paused>();
+    }
+}
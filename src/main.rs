@@ -2,41 +2,58 @@ use bevy::prelude::*;
 use crate::action::ActionPlugin;
 use crate::animation::AnimationPlugin;
 use crate::bookshelf::BookshelfPlugin;
+use crate::calendar::CalendarPlugin;
 use crate::cat::CatPlugin;
 use crate::customer::CustomerPlugin;
 use crate::debug::DebugPlugin;
 use crate::dialog::DialogPlugin;
 use crate::entity::setup;
+use crate::hud::HudPlugin;
 use crate::interaction::InteractionPlugin;
+use crate::journal::JournalPlugin;
 use crate::map::MapPlugin;
 use crate::menu::MenuPlugin;
 use crate::message_line::MessageLinePlugin;
+use crate::money::MoneyPlugin;
 use crate::movable::MovablePlugin;
 use crate::pathfinding::PathfindingPlugin;
 use crate::personality::PersonalityPlugin;
 use crate::player::PlayerPlugin;
+use crate::recipe_book::RecipeBookPlugin;
+use crate::reputation::ReputationPlugin;
 use crate::stair::StairPlugin;
+use crate::stash::StashPlugin;
+use crate::supplier::SupplierPlugin;
 use crate::tea::TeaPlugin;
 use crate::trigger::TriggerPlugin;
 
 mod action;
 mod animation;
 mod bookshelf;
+mod calendar;
 mod cat;
 mod customer;
 mod debug;
 mod dialog;
 mod entity;
+mod flavour;
 mod geom;
+mod hud;
 mod interaction;
+mod journal;
 mod map;
 mod menu;
 mod message_line;
+mod money;
 mod movable;
 mod pathfinding;
 mod personality;
 mod player;
+mod recipe_book;
+mod reputation;
 mod stair;
+mod stash;
+mod supplier;
 mod tea;
 mod trigger;
 
@@ -72,10 +89,18 @@ fn main() {
         .add_plugin(DebugPlugin)
         .add_plugin(PathfindingPlugin)
         .add_plugin(DialogPlugin)
+        .add_plugin(StashPlugin)
+        .add_plugin(SupplierPlugin)
+        .add_plugin(HudPlugin)
+        .add_plugin(MoneyPlugin)
+        .add_plugin(ReputationPlugin)
+        .add_plugin(CalendarPlugin)
         .add_plugin(MovablePlugin)
         .add_plugin(MapPlugin)
         .add_plugin(PlayerPlugin)
         .add_plugin(MenuPlugin)
+        .add_plugin(RecipeBookPlugin)
+        .add_plugin(JournalPlugin)
         .add_plugin(PersonalityPlugin)
         .add_plugin(TriggerPlugin)
         .add_plugin(ActionPlugin)
@@ -111,4 +136,8 @@ pub enum GameState {
     Processing,
     InGame,
     Dialog,
+    Picking,
+    Ordering,
+    RecipeBook,
+    Journal,
 }
@@ -2,6 +2,7 @@ use bevy::prelude::*;
 use bevy::asset::{AssetLoader, AssetPath, LoadedAsset, LoadState};
 use bevy::reflect::TypeUuid;
 use crate::GameState;
+use crate::personality::RosterHandle;
 use std::io::BufReader;
 
 pub struct MapPlugin;
@@ -29,10 +30,17 @@ impl Plugin for MapPlugin {
 
 fn transition_from_loading(
     map: Res<Map>,
+    roster: Res<RosterHandle>,
     asset_server: Res<AssetServer>,
     mut game_state: ResMut<State<crate::GameState>>,
 ) {
-    if asset_server.get_load_state(&map.handle) == LoadState::Loaded {
+    // Without a roster there's nobody to visit, so there's no point carrying on.
+    if asset_server.get_load_state(&roster.0) == LoadState::Failed {
+        panic!("Unable to load personalities.roster; see the log for details.");
+    }
+    if asset_server.get_load_state(&map.handle) == LoadState::Loaded &&
+        asset_server.get_load_state(&roster.0) == LoadState::Loaded
+    {
         game_state.set(GameState::Processing).unwrap();
     }
 }
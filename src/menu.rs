@@ -4,9 +4,11 @@ use crate::dialog::show_message_box;
 use crate::geom::HasSize;
 use crate::interaction::{PlayerInteracted, Interactable};
 use crate::movable::Movable;
+use crate::recipe_book::RecipeBook;
 use crate::tea::Ingredient;
 use rand::Rng;
 use rand::prelude::{IteratorRandom, SliceRandom};
+use std::collections::HashMap;
 
 pub struct MenuPlugin;
 
@@ -38,7 +40,7 @@ const PATTERNS: &[&str] = &[
     "Have <ingredient> Will Travel",
 ];
 
-fn generate_name(ingredients: &[(Ingredient, u32)]) -> String {
+pub fn generate_name(ingredients: &[(Ingredient, u32)]) -> String {
     let mut rng = rand::thread_rng();
     let max_ingredient = ingredients
         .iter()
@@ -53,7 +55,13 @@ fn generate_name(ingredients: &[(Ingredient, u32)]) -> String {
 
 fn generate_recipe(available: &[Ingredient], num_ingredients: usize) -> TeaRecipe {
     let mut rng = rand::thread_rng();
-    let mut ingredients = vec![];
+    // Every tea needs leaves to steep, whatever else is stirred into it.
+    let leaves = available
+        .iter()
+        .filter(|ingredient| !ingredient.is_additive())
+        .choose(&mut rng)
+        .expect("no leaves available to steep");
+    let mut ingredients = vec![(*leaves, rng.gen_range(MIN_AMOUNT..MAX_AMOUNT))];
     while ingredients.len() < num_ingredients {
         let ingredient = available.choose(&mut rng).unwrap();
         let existing = ingredients.iter().map(|(i, _)| i).find(|i| **i == *ingredient);
@@ -61,6 +69,8 @@ fn generate_recipe(available: &[Ingredient], num_ingredients: usize) -> TeaRecip
             ingredients.push((*ingredient, rng.gen_range(MIN_AMOUNT..MAX_AMOUNT)));
         }
     }
+    // Leaves are steeped first, and any additives are stirred in afterwards.
+    ingredients.sort_by_key(|(ingredient, _)| ingredient.is_additive());
     let name = generate_name(&ingredients);
     TeaRecipe {
         ingredients,
@@ -71,14 +81,24 @@ fn generate_recipe(available: &[Ingredient], num_ingredients: usize) -> TeaRecip
 fn init_menu(
     mut commands: Commands,
     ingredients: Res<StartingIngredients>,
+    book: Res<RecipeBook>,
 ) {
-    let menu = Menu {
+    let mut menu = Menu {
         teas: vec![
             generate_recipe(&ingredients.ingredients, 1),
             generate_recipe(&ingredients.ingredients, 2),
             generate_recipe(&ingredients.ingredients, 3),
         ],
     };
+    // Favourites from earlier days stay off the menu if we can't brew them today.
+    for entry in book.entries.iter().filter(|entry| entry.on_menu) {
+        let in_stock = entry.recipe.ingredients
+            .iter()
+            .all(|(ingredient, _)| ingredients.ingredients.contains(ingredient));
+        if in_stock {
+            menu.teas.push(entry.recipe.clone());
+        }
+    }
     for tea in &menu.teas {
         println!("{:?} requires: {:?}", tea.name, tea.ingredients);
     }
@@ -95,6 +115,10 @@ impl FromWorld for StartingIngredients {
         let mut ingredients = vec![];
         while ingredients.len() < 3 {
             let ingredient = Ingredient::generate_random();
+            // Make sure there are some leaves to steep.
+            if ingredients.is_empty() && ingredient.is_additive() {
+                continue;
+            }
             if !ingredients.contains(&ingredient) {
                 ingredients.push(ingredient);
             }
@@ -108,10 +132,100 @@ impl FromWorld for StartingIngredients {
 
 #[derive(Debug, Clone)]
 pub struct TeaRecipe {
+    // Additives are listed in the order they should be stirred in.
     pub ingredients: Vec<(Ingredient, u32)>,
     pub name: String,
 }
 
+// Thresholds for how closely a brew must match a recipe.
+pub const EXACT_MATCH: f32 = 0.95;
+pub const CLOSE_MATCH: f32 = 0.75;
+pub const LOOSE_MATCH: f32 = 0.5;
+
+// Extra error added for leaving out an ingredient entirely, or for including
+// one that doesn't belong, on top of the difference in teaspoons.
+const MISSING_INGREDIENT_PENALTY: f32 = 1.;
+const EXTRA_INGREDIENT_PENALTY: f32 = 0.5;
+// Extra error for stirring in the right additives in the wrong order.
+const ADDITION_ORDER_PENALTY: f32 = 1.;
+
+const BASE_PRICE: u32 = 2;
+const PRICE_PER_TEASPOON: u32 = 1;
+// What a customer pays for a tea of the player's own choosing.
+pub const RECOMMENDATION_PRICE: u32 = 5;
+
+impl TeaRecipe {
+    /// Whether both recipes call for the same amounts of the same ingredients,
+    /// with additives stirred in the same order, whatever they happen to be called.
+    pub fn same_ingredients(&self, other: &TeaRecipe) -> bool {
+        self.ingredients.len() == other.ingredients.len() &&
+            self.ingredients.iter().all(|ingredient| other.ingredients.contains(ingredient)) &&
+            self.additions() == other.additions()
+    }
+
+    /// How closely a set of ingredients matches this recipe, from 1 for an exact
+    /// match down towards 0 as the brew diverges. Errors are measured relative to
+    /// the size of the recipe, so one teaspoon too many matters less in a complex tea.
+    pub fn similarity(&self, ingredients: &HashMap<Ingredient, u32>, additions: &[Ingredient]) -> f32 {
+        let total = self.ingredients.iter().map(|(_, amount)| *amount).sum::<u32>().max(1) as f32;
+        let mut error = 0.;
+        for (ingredient, expected) in &self.ingredients {
+            error += match ingredients.get(ingredient) {
+                Some(actual) => (*expected as f32 - *actual as f32).abs(),
+                None => *expected as f32 + MISSING_INGREDIENT_PENALTY,
+            };
+        }
+        for (ingredient, actual) in ingredients {
+            if self.ingredients.iter().all(|(i, _)| i != ingredient) {
+                error += *actual as f32 + EXTRA_INGREDIENT_PENALTY;
+            }
+        }
+        if !self.additions_in_order(additions) {
+            error += ADDITION_ORDER_PENALTY;
+        }
+        1. / (1. + error / total)
+    }
+
+    pub fn price(&self) -> u32 {
+        let teaspoons = self.ingredients.iter().map(|(_, amount)| *amount).sum::<u32>();
+        BASE_PRICE + teaspoons * PRICE_PER_TEASPOON
+    }
+
+    pub fn additions(&self) -> Vec<Ingredient> {
+        self.ingredients
+            .iter()
+            .map(|(ingredient, _)| *ingredient)
+            .filter(Ingredient::is_additive)
+            .collect()
+    }
+
+    /// Whether the additives shared by this recipe and a brew were stirred in
+    /// in the same order. Missing or extra additives are accounted for separately.
+    pub fn additions_in_order(&self, additions: &[Ingredient]) -> bool {
+        let expected = self.additions()
+            .into_iter()
+            .filter(|ingredient| additions.contains(ingredient))
+            .collect::<Vec<_>>();
+        let actual = additions
+            .iter()
+            .filter(|ingredient| expected.contains(ingredient))
+            .cloned()
+            .collect::<Vec<_>>();
+        expected == actual
+    }
+
+    pub fn describe_ingredients(&self) -> Vec<String> {
+        self.ingredients
+            .iter()
+            .map(|(ingredient, amount)| if ingredient.is_additive() {
+                format!("then stir in {} tsp of {:?}", amount, ingredient)
+            } else {
+                format!("{} tsp of {:?}", amount, ingredient)
+            })
+            .collect()
+    }
+}
+
 #[derive(Resource, Debug)]
 pub struct Menu {
     pub teas: Vec<TeaRecipe>,
@@ -132,9 +246,13 @@ fn interact_with_menu(
         if let Ok(menu_entity) = menu_entity.get(event.interacted_entity) {
             let conversation = menu.teas.iter()
                 .map(|recipe| {
-                    let mut dialogue = format!("{}\n\nIngredients:", recipe.name);
-                    for (ingredient, amount) in &recipe.ingredients {
-                        dialogue += &format!("\n{:?} tsp of {:?}", amount, ingredient);
+                    let mut dialogue = format!(
+                        "{} ({} coins)\n\nIngredients:",
+                        recipe.name,
+                        recipe.price(),
+                    );
+                    for step in recipe.describe_ingredients() {
+                        dialogue += &format!("\n{}", step);
                     }
                     dialogue
                 })
@@ -163,3 +281,52 @@ pub fn spawn_menu(
         transform,
     ));
 }
+
+#[test]
+fn recipe_similarity() {
+    let recipe = TeaRecipe {
+        ingredients: vec![(Ingredient::BlackTea, 2), (Ingredient::Milk, 1)],
+        name: "Test".to_owned(),
+    };
+    let milk = [Ingredient::Milk];
+    let exact = HashMap::from([(Ingredient::BlackTea, 2), (Ingredient::Milk, 1)]);
+    assert_eq!(recipe.similarity(&exact, &milk), 1.);
+
+    let extra_spoon = HashMap::from([(Ingredient::BlackTea, 3), (Ingredient::Milk, 1)]);
+    let missing = HashMap::from([(Ingredient::BlackTea, 2)]);
+    let wrong = HashMap::from([(Ingredient::Lemon, 3)]);
+    assert!(recipe.similarity(&extra_spoon, &milk) > recipe.similarity(&missing, &[]));
+    assert!(recipe.similarity(&missing, &[]) > recipe.similarity(&wrong, &[Ingredient::Lemon]));
+    assert!(recipe.similarity(&wrong, &[Ingredient::Lemon]) < 0.5);
+}
+
+#[test]
+fn recipe_addition_order() {
+    let recipe = TeaRecipe {
+        ingredients: vec![(Ingredient::BlackTea, 2), (Ingredient::Honey, 1), (Ingredient::Milk, 1)],
+        name: "Test".to_owned(),
+    };
+    assert!(recipe.additions_in_order(&[Ingredient::Honey, Ingredient::Milk]));
+    assert!(recipe.additions_in_order(&[Ingredient::Sugar, Ingredient::Milk]));
+    assert!(!recipe.additions_in_order(&[Ingredient::Milk, Ingredient::Honey]));
+
+    let ingredients = HashMap::from([
+        (Ingredient::BlackTea, 2), (Ingredient::Honey, 1), (Ingredient::Milk, 1),
+    ]);
+    assert!(
+        recipe.similarity(&ingredients, &[Ingredient::Honey, Ingredient::Milk]) >
+            recipe.similarity(&ingredients, &[Ingredient::Milk, Ingredient::Honey])
+    );
+}
+
+#[test]
+fn menu_teas_can_be_steeped() {
+    for _ in 0..100 {
+        let starting = StartingIngredients::from_world(&mut World::new());
+        assert!(starting.ingredients.iter().any(|ingredient| !ingredient.is_additive()));
+        for num_ingredients in 1..=3 {
+            let recipe = generate_recipe(&starting.ingredients, num_ingredients);
+            assert!(recipe.ingredients.iter().any(|(ingredient, _)| !ingredient.is_additive()));
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use bevy::prelude::*;
+use crate::entity::{Reaction, RelationshipStatus};
+
+pub struct MoneyPlugin;
+
+impl Plugin for MoneyPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Money>();
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Money {
+    pub coins: u32,
+    // Everything earned since the teahouse opened, including tips.
+    pub earned: u32,
+}
+
+impl Money {
+    pub fn earn(&mut self, coins: u32) {
+        self.coins += coins;
+        self.earned += coins;
+    }
+
+    // Returns false, leaving the coins untouched, if there aren't enough of them.
+    pub fn spend(&mut self, coins: u32) -> bool {
+        if coins > self.coins {
+            return false;
+        }
+        self.coins -= coins;
+        true
+    }
+}
+
+/// How much a customer leaves on top of the price of their tea. Customers
+/// only tip for a brew they enjoyed, and friends are more generous.
+pub fn tip(reaction: Reaction, status: RelationshipStatus) -> u32 {
+    let base = match reaction {
+        Reaction::MajorPositive => 2,
+        Reaction::Positive => 1,
+        Reaction::Negative | Reaction::MajorNegative => return 0,
+    };
+    let generosity = match status {
+        RelationshipStatus::Angry => 0,
+        RelationshipStatus::Neutral | RelationshipStatus::Friendly => 1,
+        RelationshipStatus::VeryFriendly => 2,
+        RelationshipStatus::Crushing => 3,
+    };
+    base * generosity
+}
+
+#[test]
+fn tips_depend_on_reaction_and_relationship() {
+    assert_eq!(tip(Reaction::MajorPositive, RelationshipStatus::Crushing), 6);
+    assert_eq!(tip(Reaction::Positive, RelationshipStatus::Neutral), 1);
+    assert_eq!(tip(Reaction::MajorPositive, RelationshipStatus::Angry), 0);
+    assert_eq!(tip(Reaction::Negative, RelationshipStatus::Crushing), 0);
+}
+
+#[test]
+fn spending_needs_enough_coins() {
+    let mut money = Money::default();
+    money.earn(5);
+    assert!(!money.spend(6));
+    assert!(money.spend(5));
+    assert_eq!(money.coins, 0);
+    assert_eq!(money.earned, 5);
+}
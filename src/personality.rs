@@ -1,55 +1,358 @@
 use bevy::prelude::*;
-use crate::entity::{Affection, RelationshipStatus};
-use rand_derive2::RandGen;
+use bevy::asset::{AssetLoader, LoadedAsset};
+use bevy::reflect::TypeUuid;
+use crate::GameState;
+use crate::calendar::{Calendar, DAYS_PER_YEAR};
+use crate::entity::{Affection, Reaction, RelationshipStatus};
+use crate::tea::Ingredient;
+use rand::Rng;
 use std::collections::HashMap;
-use strum::IntoEnumIterator;
-use strum::EnumIter;
+use std::fmt;
+use std::str::FromStr;
 
 pub struct PersonalityPlugin;
 
 impl Plugin for PersonalityPlugin {
     fn build(&self, app: &mut App) {
         app
-            .add_startup_system(init_personalities);
+            .add_asset::<Roster>()
+            .add_asset_loader(RosterLoader)
+            .init_resource::<Personalities>()
+            .add_event::<RelationshipMilestoneEvent>()
+            .add_startup_system(load_roster)
+            .add_system(decay_affection)
+            .add_system(detect_milestones)
+            .add_system_set(
+                SystemSet::on_enter(GameState::Processing)
+                    .with_system(init_personalities)
+            );
     }
 }
 
-#[derive(Debug, Hash, EnumIter, RandGen, PartialEq, Eq)]
-pub enum Personality {
-    Frieda,
-    Lucien,
-    Wednesdaeigh,
-    Xiaoshan,
+// Personalities are identified by their name in the roster.
+#[derive(Clone, Hash, PartialEq, Eq, Debug)]
+pub struct PersonalityId(pub String);
+
+impl fmt::Display for PersonalityId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// Everything the roster file says about a single personality.
+#[derive(Clone, Debug)]
+pub struct PersonalityData {
+    pub id: PersonalityId,
+    pub sprite: String,
+    pub dialogue: Vec<String>,
+    pub likes: Vec<Ingredient>,
+    pub dislikes: Vec<Ingredient>,
+    // How long this personality is willing to wait, relative to other customers.
+    pub patience: f32,
+    // How often this personality visits, relative to the others.
+    pub spawn_weight: u32,
+}
+
+#[derive(TypeUuid)]
+#[uuid = "3f0f5b7e-5a3c-4d8e-9d43-6a9e2b1c7f21"]
+pub struct Roster {
+    pub personalities: Vec<PersonalityData>,
+}
+
+// The sprite sheet used by any personality without one of their own.
+pub const DEFAULT_SPRITE: &str = "woman.png";
+
+// The roster is a series of sections, one per personality:
+//
+//   [Name]
+//   sprite = woman.png
+//   patience = 1.0
+//   spawn_weight = 2
+//   likes = Honey, Milk
+//   dislikes = Lemon
+//   dialogue = A line of small talk.
+//
+// Dialogue may be repeated to add more lines. Blank lines and lines starting
+// with # are ignored.
+fn parse_roster(text: &str) -> anyhow::Result<Roster> {
+    let mut personalities: Vec<PersonalityData> = vec![];
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            personalities.push(PersonalityData {
+                id: PersonalityId(name.trim().to_owned()),
+                sprite: DEFAULT_SPRITE.to_owned(),
+                dialogue: vec![],
+                likes: vec![],
+                dislikes: vec![],
+                patience: 1.,
+                spawn_weight: 1,
+            });
+            continue;
+        }
+
+        let personality = personalities
+            .last_mut()
+            .ok_or_else(|| anyhow::anyhow!("line {}: expected a [Name] section", number + 1))?;
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("line {}: expected key = value", number + 1))?;
+        let value = value.trim();
+        match key.trim() {
+            "sprite" => personality.sprite = value.to_owned(),
+            "dialogue" => personality.dialogue.push(value.to_owned()),
+            "likes" => personality.likes = parse_ingredients(value)?,
+            "dislikes" => personality.dislikes = parse_ingredients(value)?,
+            "patience" => personality.patience = value.parse()?,
+            "spawn_weight" => personality.spawn_weight = value.parse()?,
+            key => anyhow::bail!("line {}: unknown key {}", number + 1, key),
+        }
+    }
+    Ok(Roster {
+        personalities,
+    })
+}
+
+fn parse_ingredients(value: &str) -> anyhow::Result<Vec<Ingredient>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|ingredient| !ingredient.is_empty())
+        .map(|ingredient| {
+            Ingredient::from_str(ingredient)
+                .map_err(|_| anyhow::anyhow!("unknown ingredient {}", ingredient))
+        })
+        .collect()
+}
+
+pub struct RosterLoader;
+
+impl AssetLoader for RosterLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut bevy::asset::LoadContext,
+    ) -> bevy::asset::BoxedFuture<'a, anyhow::Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let roster = parse_roster(std::str::from_utf8(bytes)?)?;
+            load_context.set_default_asset(LoadedAsset::new(roster));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        static EXTENSIONS: &[&str] = &["roster"];
+        EXTENSIONS
+    }
+}
+
+#[derive(Resource)]
+pub struct RosterHandle(pub Handle<Roster>);
+
+fn load_roster(
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    commands.insert_resource(RosterHandle(asset_server.load("personalities.roster")));
+}
+
+// A tea that was served to a personality, and what they thought of it.
+#[derive(Clone)]
+pub struct ServedTea {
+    // None when the customer asked for a recommendation.
+    pub recipe: Option<String>,
+    pub reaction: Reaction,
+}
+
+impl ServedTea {
+    pub fn describe(&self) -> String {
+        match &self.recipe {
+            Some(name) => format!("the {}", name),
+            None => "a tea you recommended".to_owned(),
+        }
+    }
 }
 
 #[derive(Default)]
 pub struct State {
     pub affection: Affection,
-    pub likes: (),
-    pub dislikes: (),
+    pub likes: Vec<Ingredient>,
+    pub dislikes: Vec<Ingredient>,
+    // The preferences that the player has found out about.
+    pub known_likes: Vec<Ingredient>,
+    pub known_dislikes: Vec<Ingredient>,
     pub visits: u32,
-    pub birthday: (),
+    // The day of the year on which this personality celebrates.
+    pub birthday: u32,
+    pub known_birthday: bool,
+    pub served: Vec<ServedTea>,
+    // The calendar day of the most recent visit.
+    pub last_visit: Option<u32>,
+    // The relationship milestones that have already been celebrated.
+    pub milestones: Vec<RelationshipStatus>,
+    pub sprite: String,
+    pub dialogue: Vec<String>,
+    pub patience: f32,
+    pub spawn_weight: u32,
 }
 
-#[derive(Resource)]
+// Customers who have visited this many times expect to be remembered.
+const REGULAR_VISITS: u32 = 3;
+// Regulars start to drift away after this many days without a visit.
+const ABSENCE_DAYS: u32 = 3;
+const DAILY_AFFECTION_DECAY: f32 = 0.25;
+
+const MILESTONES: &[RelationshipStatus] = &[
+    RelationshipStatus::Friendly,
+    RelationshipStatus::VeryFriendly,
+    RelationshipStatus::Crushing,
+];
+
+impl State {
+    pub fn is_regular(&self) -> bool {
+        self.visits >= REGULAR_VISITS
+    }
+
+    pub fn learn(&mut self, ingredient: Ingredient) {
+        if self.likes.contains(&ingredient) && !self.known_likes.contains(&ingredient) {
+            self.known_likes.push(ingredient);
+        }
+        if self.dislikes.contains(&ingredient) && !self.known_dislikes.contains(&ingredient) {
+            self.known_dislikes.push(ingredient);
+        }
+    }
+
+    // Any milestones reached for the first time since the last check.
+    fn new_milestones(&mut self) -> Vec<RelationshipStatus> {
+        let status = self.affection.status();
+        let reached = MILESTONES
+            .iter()
+            .filter(|milestone| status >= **milestone && !self.milestones.contains(milestone))
+            .cloned()
+            .collect::<Vec<_>>();
+        self.milestones.extend(reached.iter().cloned());
+        reached
+    }
+}
+
+// Sent the first time a personality's relationship with the player reaches a
+// new level, so that scripts and dialogue can respond.
+pub struct RelationshipMilestoneEvent {
+    pub personality: PersonalityId,
+    pub status: RelationshipStatus,
+}
+
+#[derive(Resource, Default)]
 pub struct Personalities {
-    pub data: HashMap<Personality, State>
+    pub data: HashMap<PersonalityId, State>
 }
 
 fn init_personalities(
-    mut commands: Commands,
+    roster: Res<RosterHandle>,
+    rosters: Res<Assets<Roster>>,
+    mut personalities: ResMut<Personalities>,
 ) {
-    let mut data = HashMap::new();
-    for name in Personality::iter() {
+    let roster = rosters.get(&roster.0).unwrap();
+    let mut rng = rand::thread_rng();
+    for personality in &roster.personalities {
         let relationship = RelationshipStatus::generate_random();
-        data.insert(name, State {
+        let mut state = State {
             affection: relationship.into(),
-            ..default() 
-        });
+            likes: personality.likes.clone(),
+            dislikes: personality.dislikes.clone(),
+            birthday: rng.gen_range(0..DAYS_PER_YEAR),
+            sprite: personality.sprite.clone(),
+            dialogue: personality.dialogue.clone(),
+            patience: personality.patience,
+            spawn_weight: personality.spawn_weight,
+            ..default()
+        };
+        // Only relationships that grow during play are worth celebrating.
+        state.new_milestones();
+        personalities.data.insert(personality.id.clone(), state);
+    }
+}
+
+fn decay_affection(
+    calendar: Res<Calendar>,
+    mut personalities: ResMut<Personalities>,
+) {
+    if !calendar.is_changed() {
+        return;
+    }
+    for state in personalities.data.values_mut() {
+        let absent = match state.last_visit {
+            Some(day) => calendar.day.saturating_sub(day) > ABSENCE_DAYS,
+            None => false,
+        };
+        if state.is_regular() && absent {
+            state.affection.decay(DAILY_AFFECTION_DECAY);
+        }
+    }
+}
+
+fn detect_milestones(
+    mut personalities: ResMut<Personalities>,
+    mut milestone_events: EventWriter<RelationshipMilestoneEvent>,
+) {
+    if !personalities.is_changed() {
+        return;
     }
+    for (id, state) in personalities.data.iter_mut() {
+        for status in state.new_milestones() {
+            milestone_events.send(RelationshipMilestoneEvent {
+                personality: id.clone(),
+                status,
+            });
+        }
+    }
+}
+
+#[test]
+fn parse_roster_sections() {
+    let roster = parse_roster(
+        "# A comment\n\
+         [Frieda]\n\
+         likes = Honey, Milk\n\
+         dialogue = Hello!\n\
+         dialogue = Lovely weather.\n\
+         \n\
+         [Lucien]\n\
+         patience = 0.5\n\
+         spawn_weight = 3\n"
+    ).unwrap();
+    assert_eq!(roster.personalities.len(), 2);
+    let frieda = &roster.personalities[0];
+    assert_eq!(frieda.id, PersonalityId("Frieda".to_owned()));
+    assert_eq!(frieda.likes, vec![Ingredient::Honey, Ingredient::Milk]);
+    assert_eq!(frieda.dialogue.len(), 2);
+    assert_eq!(roster.personalities[1].patience, 0.5);
+    assert_eq!(roster.personalities[1].spawn_weight, 3);
+
+    assert!(parse_roster("likes = Honey").is_err());
+    assert!(parse_roster("[Frieda]\nlikes = Toast").is_err());
+}
+
+#[test]
+fn milestones_are_reached_once() {
+    let mut state = State::default();
+    assert!(state.new_milestones().is_empty());
+
+    state.affection.react(Reaction::MajorPositive);
+    state.affection.react(Reaction::MajorPositive);
+    assert_eq!(state.new_milestones(), vec![RelationshipStatus::Friendly]);
+    assert!(state.new_milestones().is_empty());
+
+    state.affection.react(Reaction::MajorNegative);
+    state.affection.react(Reaction::MajorPositive);
+    assert!(state.new_milestones().is_empty());
+}
 
-    let personalities = Personalities {
-        data,
-    };
-    commands.insert_resource(personalities);
+#[test]
+fn bundled_roster_parses() {
+    let roster = parse_roster(include_str!("../assets/personalities.roster")).unwrap();
+    assert!(!roster.personalities.is_empty());
 }
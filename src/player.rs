@@ -17,7 +17,11 @@ pub struct Holding {
 
 #[derive(Component, Default)]
 pub struct Player {
-    pub carrying: HashMap<Ingredient, u32>,
+    // The freshness of each teaspoon of each ingredient being carried.
+    pub carrying: HashMap<Ingredient, Vec<f32>>,
+    // Carried additives in the order they were measured out, which is the
+    // order they get stirred into the tea.
+    pub additives: Vec<Ingredient>,
 }
 
 pub struct PlayerPlugin;
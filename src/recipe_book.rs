@@ -0,0 +1,374 @@
+use bevy::prelude::*;
+use crate::GameState;
+use crate::dialog::{Panel, spawn_panel};
+use crate::entity::Reaction;
+use crate::menu::{Menu, TeaRecipe, EXACT_MATCH, generate_name};
+use crate::message_line::{DEFAULT_EXPIRY, StatusEvent};
+use crate::tea::Ingredient;
+use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::str::FromStr;
+
+pub struct RecipeBookPlugin;
+
+impl Plugin for RecipeBookPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<RecipeBook>()
+            .add_event::<BrewRatedEvent>()
+            .add_system(record_ratings)
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(open_recipe_book)
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::RecipeBook)
+                    .with_system(run_recipe_book)
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::RecipeBook)
+                    .with_system(exit_recipe_book)
+            );
+    }
+}
+
+pub struct RecipeBookEntry {
+    pub recipe: TeaRecipe,
+    pub ratings: Vec<i32>,
+    // The customers who enjoyed this tea.
+    pub fans: Vec<String>,
+    pub on_menu: bool,
+}
+
+impl RecipeBookEntry {
+    fn average_rating(&self) -> f32 {
+        if self.ratings.is_empty() {
+            return 0.;
+        }
+        self.ratings.iter().sum::<i32>() as f32 / self.ratings.len() as f32
+    }
+}
+
+// Recipes the player has discovered by serving brews that customers loved.
+#[derive(Resource)]
+pub struct RecipeBook {
+    pub entries: Vec<RecipeBookEntry>,
+}
+
+impl FromWorld for RecipeBook {
+    fn from_world(_world: &mut World) -> Self {
+        load_recipe_book()
+    }
+}
+
+// Sent whenever a customer passes judgement on a tea they were served.
+pub struct BrewRatedEvent {
+    pub customer_entity: Entity,
+    pub customer: String,
+    pub ingredients: HashMap<Ingredient, u32>,
+    pub additions: Vec<Ingredient>,
+    pub reaction: Reaction,
+}
+
+fn rating_score(reaction: Reaction) -> i32 {
+    match reaction {
+        Reaction::MajorPositive => 2,
+        Reaction::Positive => 1,
+        Reaction::Negative => -1,
+        Reaction::MajorNegative => -2,
+    }
+}
+
+fn record_ratings(
+    mut events: EventReader<BrewRatedEvent>,
+    mut book: ResMut<RecipeBook>,
+    menu: Res<Menu>,
+    mut status_events: EventWriter<StatusEvent>,
+) {
+    for event in events.iter() {
+        let score = rating_score(event.reaction);
+        let existing = book.entries
+            .iter_mut()
+            .find(|entry| entry.recipe.similarity(&event.ingredients, &event.additions) >= EXACT_MATCH);
+        if let Some(entry) = existing {
+            entry.ratings.push(score);
+            if score > 0 && !entry.fans.contains(&event.customer) {
+                entry.fans.push(event.customer.clone());
+            }
+            save_recipe_book(&book);
+            continue;
+        }
+
+        // Only brews that a customer loved, and that aren't already known, are worth writing down.
+        if event.reaction != Reaction::MajorPositive {
+            continue;
+        }
+        if menu.teas.iter().any(|recipe| recipe.similarity(&event.ingredients, &event.additions) >= EXACT_MATCH) {
+            continue;
+        }
+
+        let mut ingredients = event.ingredients
+            .iter()
+            .filter(|(ingredient, _)| !ingredient.is_additive())
+            .map(|(ingredient, amount)| (*ingredient, *amount))
+            .collect::<Vec<_>>();
+        ingredients.sort_by_key(|(ingredient, _)| format!("{:?}", ingredient));
+        // Additives are written down in the order they were stirred in.
+        for ingredient in &event.additions {
+            ingredients.push((*ingredient, event.ingredients[ingredient]));
+        }
+        let name = generate_name(&ingredients);
+        status_events.send(StatusEvent::timed_message(
+            event.customer_entity,
+            format!("You discovered a new recipe: {}! Press B to see your recipe book.", name),
+            DEFAULT_EXPIRY,
+        ));
+        book.entries.push(RecipeBookEntry {
+            recipe: TeaRecipe {
+                ingredients,
+                name,
+            },
+            ratings: vec![score],
+            fans: vec![event.customer.clone()],
+            on_menu: false,
+        });
+        save_recipe_book(&book);
+    }
+}
+
+#[derive(Component)]
+struct RecipeBookText;
+
+#[derive(Component)]
+struct RecipeBookPage {
+    index: usize,
+    box_entity: Entity,
+}
+
+fn open_recipe_book(
+    keys: Res<Input<KeyCode>>,
+    mut game_state: ResMut<State<GameState>>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    if !keys.just_released(KeyCode::B) {
+        return;
+    }
+    game_state.set(GameState::RecipeBook).unwrap();
+
+    let id = spawn_panel(&mut commands, &asset_server, RecipeBookText, Panel {
+        hint: "Left/right to turn the page, space to add to or remove from the menu, \
+               backspace to close.",
+        height: 260.0,
+        font_size: 20.0,
+        background: Color::rgb(0.5, 0.35, 0.25),
+        hint_color: Color::rgb(1.0, 0.9, 0.8),
+        ..default()
+    });
+
+    commands.spawn(RecipeBookPage {
+        index: 0,
+        box_entity: id,
+    });
+}
+
+fn describe_entry(book: &RecipeBook, menu: &Menu, index: usize) -> String {
+    let entry = match book.entries.get(index) {
+        Some(entry) => entry,
+        None => return "Your recipe book is empty.\n\nCustomers who ask for a recommendation \
+                        might inspire something new.".to_owned(),
+    };
+    let ingredients = entry.recipe.describe_ingredients().join(", ");
+    let fans = if entry.fans.is_empty() {
+        "nobody yet".to_owned()
+    } else {
+        entry.fans.join(", ")
+    };
+    let status = if !entry.on_menu {
+        "Not on the menu."
+    } else if menu.teas.iter().any(|recipe| recipe.same_ingredients(&entry.recipe)) {
+        "On the menu."
+    } else {
+        "On the menu, but some of its ingredients weren't in stock today."
+    };
+    format!(
+        "Recipe book ({}/{})\n\n{}\n{}\nRating: {:.1} from {} servings\nEnjoyed by: {}\n{}",
+        index + 1,
+        book.entries.len(),
+        entry.recipe.name,
+        ingredients,
+        entry.average_rating(),
+        entry.ratings.len(),
+        fans,
+        status,
+    )
+}
+
+fn run_recipe_book(
+    mut page: Query<&mut RecipeBookPage>,
+    mut text_box: Query<&mut Text, With<RecipeBookText>>,
+    mut book: ResMut<RecipeBook>,
+    mut menu: ResMut<Menu>,
+    keys: Res<Input<KeyCode>>,
+    mut game_state: ResMut<State<GameState>>,
+) {
+    let mut page = page.single_mut();
+    let mut text_box = text_box.single_mut();
+    let pages = book.entries.len();
+
+    if pages > 0 {
+        if keys.just_released(KeyCode::Left) {
+            page.index = (page.index + pages - 1) % pages;
+        }
+        if keys.just_released(KeyCode::Right) {
+            page.index = (page.index + 1) % pages;
+        }
+        if keys.just_released(KeyCode::Space) {
+            let entry = &mut book.entries[page.index];
+            entry.on_menu = !entry.on_menu;
+            if entry.on_menu {
+                menu.teas.push(entry.recipe.clone());
+            } else {
+                menu.teas.retain(|recipe| !recipe.same_ingredients(&entry.recipe));
+            }
+            save_recipe_book(&book);
+        }
+    }
+
+    text_box.sections[0].value = describe_entry(&book, &menu, page.index);
+
+    if keys.just_released(KeyCode::Back) {
+        game_state.set(GameState::InGame).unwrap();
+    }
+}
+
+fn exit_recipe_book(
+    page: Query<(Entity, &RecipeBookPage)>,
+    mut commands: Commands,
+) {
+    let (entity, page) = page.single();
+    commands.entity(page.box_entity).despawn_recursive();
+    commands.entity(entity).despawn();
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+const RECIPE_BOOK_PATH: &str = "recipe_book.txt";
+
+// Each entry is stored on a single line as tab-separated fields:
+// name, ingredient:amount pairs, ratings, fans, and whether it is on the menu.
+#[cfg(not(target_arch = "wasm32"))]
+fn format_entry(entry: &RecipeBookEntry) -> String {
+    let ingredients = entry.recipe.ingredients
+        .iter()
+        .map(|(ingredient, amount)| format!("{:?}:{}", ingredient, amount))
+        .collect::<Vec<_>>()
+        .join(",");
+    let ratings = entry.ratings
+        .iter()
+        .map(|rating| rating.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    format!(
+        "{}\t{}\t{}\t{}\t{}",
+        entry.recipe.name,
+        ingredients,
+        ratings,
+        entry.fans.join(","),
+        if entry.on_menu { 1 } else { 0 },
+    )
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_entry(line: &str) -> Option<RecipeBookEntry> {
+    let mut fields = line.split('\t');
+    let name = fields.next()?.to_owned();
+    let ingredients = fields.next()?
+        .split(',')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (ingredient, amount) = pair.split_once(':')?;
+            Some((Ingredient::from_str(ingredient).ok()?, amount.parse().ok()?))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let ratings = fields.next()?
+        .split(',')
+        .filter(|rating| !rating.is_empty())
+        .map(|rating| rating.parse().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let fans = fields.next()?
+        .split(',')
+        .filter(|fan| !fan.is_empty())
+        .map(str::to_owned)
+        .collect();
+    let on_menu = fields.next()? == "1";
+    Some(RecipeBookEntry {
+        recipe: TeaRecipe {
+            ingredients,
+            name,
+        },
+        ratings,
+        fans,
+        on_menu,
+    })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_recipe_book() -> RecipeBook {
+    let contents = match std::fs::read_to_string(RECIPE_BOOK_PATH) {
+        Ok(contents) => contents,
+        Err(_) => return RecipeBook { entries: vec![] },
+    };
+    let entries = contents
+        .lines()
+        .filter_map(|line| {
+            let entry = parse_entry(line);
+            if entry.is_none() {
+                warn!("Ignoring malformed recipe book entry: {}", line);
+            }
+            entry
+        })
+        .collect();
+    RecipeBook { entries }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_recipe_book(book: &RecipeBook) {
+    let contents = book.entries
+        .iter()
+        .map(format_entry)
+        .collect::<Vec<_>>()
+        .join("\n");
+    if let Err(e) = std::fs::write(RECIPE_BOOK_PATH, contents) {
+        warn!("Unable to save recipe book: {}", e);
+    }
+}
+
+// FIXME: persist the recipe book in browser storage.
+#[cfg(target_arch = "wasm32")]
+fn load_recipe_book() -> RecipeBook {
+    RecipeBook { entries: vec![] }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn save_recipe_book(_book: &RecipeBook) {
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn recipe_book_entry_round_trip() {
+    let entry = RecipeBookEntry {
+        recipe: TeaRecipe {
+            ingredients: vec![(Ingredient::GreenTea, 2), (Ingredient::Honey, 1)],
+            name: "A GreenTea-y Hug".to_owned(),
+        },
+        ratings: vec![2, -1],
+        fans: vec!["Frieda".to_owned()],
+        on_menu: true,
+    };
+    let parsed = parse_entry(&format_entry(&entry)).unwrap();
+    assert_eq!(parsed.recipe.name, entry.recipe.name);
+    assert_eq!(parsed.recipe.ingredients, entry.recipe.ingredients);
+    assert_eq!(parsed.ratings, entry.ratings);
+    assert_eq!(parsed.fans, entry.fans);
+    assert!(parsed.on_menu);
+}
@@ -0,0 +1,143 @@
+use bevy::prelude::*;
+use crate::entity::{Reaction, RelationshipStatus};
+use crate::recipe_book::BrewRatedEvent;
+use crate::tea::Dirty;
+use std::time::Duration;
+
+pub struct ReputationPlugin;
+
+impl Plugin for ReputationPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Reputation>()
+            .add_event::<LostPatienceEvent>()
+            .add_system(rate_service)
+            .add_system(inspect_cleanliness);
+    }
+}
+
+const MAX_REPUTATION: f32 = 10.;
+const LOST_PATIENCE_PENALTY: f32 = 1.5;
+
+// How often customers take note of the state of the teahouse.
+const INSPECTION_INTERVAL: Duration = Duration::from_secs(20);
+// A couple of cups waiting to be washed is only to be expected.
+const DIRTY_TOLERANCE: usize = 2;
+const MESS_PENALTY: f32 = 0.1;
+const TIDY_BONUS: f32 = 0.1;
+
+#[derive(Resource)]
+pub struct Reputation {
+    score: f32,
+    inspection: Timer,
+}
+
+impl Default for Reputation {
+    fn default() -> Self {
+        Self {
+            score: 0.,
+            inspection: Timer::new(INSPECTION_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+impl Reputation {
+    fn adjust(&mut self, amount: f32) {
+        self.score = (self.score + amount).clamp(-MAX_REPUTATION, MAX_REPUTATION);
+    }
+
+    /// From -1 for a teahouse that everybody avoids, to 1 for the talk of the town.
+    pub fn standing(&self) -> f32 {
+        self.score / MAX_REPUTATION
+    }
+
+    /// How busy the teahouse is compared to usual, from half as busy to half as
+    /// busy again.
+    pub fn busyness(&self) -> f32 {
+        1. + self.standing() / 2.
+    }
+
+    /// How likely a personality is to visit. Word of a good reputation draws in
+    /// everybody, while a bad one keeps away all but the most loyal friends.
+    pub fn visit_weight(&self, spawn_weight: u32, status: RelationshipStatus) -> f32 {
+        let loyalty = match status {
+            RelationshipStatus::Angry => 0.,
+            RelationshipStatus::Neutral => 0.25,
+            RelationshipStatus::Friendly => 0.5,
+            RelationshipStatus::VeryFriendly => 0.75,
+            RelationshipStatus::Crushing => 1.,
+        };
+        spawn_weight as f32 * (1. + self.standing() * (1. - loyalty))
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self.standing() {
+            standing if standing < -0.5 => "Shunned",
+            standing if standing < -0.1 => "Poor",
+            standing if standing <= 0.1 => "Modest",
+            standing if standing <= 0.5 => "Good",
+            _ => "Renowned",
+        }
+    }
+}
+
+// Sent when a customer gives up waiting for their tea.
+pub struct LostPatienceEvent;
+
+fn rating_change(reaction: Reaction) -> f32 {
+    match reaction {
+        Reaction::MajorPositive => 1.,
+        Reaction::Positive => 0.5,
+        Reaction::Negative => -0.5,
+        Reaction::MajorNegative => -1.,
+    }
+}
+
+fn rate_service(
+    mut rated_events: EventReader<BrewRatedEvent>,
+    mut lost_patience_events: EventReader<LostPatienceEvent>,
+    mut reputation: ResMut<Reputation>,
+) {
+    for event in rated_events.iter() {
+        reputation.adjust(rating_change(event.reaction));
+    }
+    for _event in lost_patience_events.iter() {
+        reputation.adjust(-LOST_PATIENCE_PENALTY);
+    }
+}
+
+fn inspect_cleanliness(
+    dirty: Query<(), With<Dirty>>,
+    mut reputation: ResMut<Reputation>,
+    time: Res<Time>,
+) {
+    // Only a change in score is worth reacting to.
+    reputation.bypass_change_detection().inspection.tick(time.delta());
+    if !reputation.inspection.just_finished() {
+        return;
+    }
+    let mess = dirty.iter().count();
+    if mess == 0 {
+        reputation.adjust(TIDY_BONUS);
+    } else if mess > DIRTY_TOLERANCE {
+        reputation.adjust(-MESS_PENALTY * (mess - DIRTY_TOLERANCE) as f32);
+    }
+}
+
+#[test]
+fn reputation_shapes_who_visits() {
+    let mut reputation = Reputation::default();
+    assert_eq!(reputation.visit_weight(2, RelationshipStatus::Angry), 2.);
+    assert_eq!(reputation.busyness(), 1.);
+
+    reputation.adjust(-100.);
+    assert_eq!(reputation.describe(), "Shunned");
+    assert_eq!(reputation.visit_weight(2, RelationshipStatus::Angry), 0.);
+    assert_eq!(reputation.visit_weight(2, RelationshipStatus::Crushing), 2.);
+    assert!(reputation.busyness() < 1.);
+
+    reputation.adjust(200.);
+    assert_eq!(reputation.describe(), "Renowned");
+    assert!(reputation.visit_weight(2, RelationshipStatus::Neutral) > 2.);
+    assert!(reputation.busyness() > 1.);
+}
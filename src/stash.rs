@@ -0,0 +1,160 @@
+use bevy::prelude::*;
+use crate::GameState;
+use crate::dialog::{Panel, spawn_panel};
+use crate::message_line::{DEFAULT_EXPIRY, StatusEvent};
+use crate::player::Player;
+use crate::tea::TeaStash;
+
+pub struct StashPlugin;
+
+impl Plugin for StashPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_system_set(
+                SystemSet::on_update(GameState::Picking)
+                    .with_system(run_picker)
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Picking)
+                    .with_system(exit_picker)
+            );
+    }
+}
+
+#[derive(Component)]
+struct PickerText;
+
+// The amount the player has chosen to move between a stash and their hands.
+// Positive amounts are taken from the stash; negative ones are put back.
+#[derive(Component)]
+struct StashPicker {
+    stash: Entity,
+    player: Entity,
+    amount: i32,
+    box_entity: Entity,
+}
+
+pub fn show_stash_picker(
+    stash_entity: Entity,
+    player_entity: Entity,
+    stash: &TeaStash,
+    player: &Player,
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+) {
+    let id = spawn_panel(commands, asset_server, PickerText, Panel {
+        text: describe_choice(stash, player, 0),
+        hint: "Left/right to choose, space to confirm, backspace to cancel.",
+        background: Color::rgb(0.4, 0.6, 0.4),
+        hint_color: Color::rgb(0.8, 1.0, 0.8),
+        ..default()
+    });
+
+    commands.spawn(StashPicker {
+        stash: stash_entity,
+        player: player_entity,
+        amount: 0,
+        box_entity: id,
+    });
+}
+
+fn carried(stash: &TeaStash, player: &Player) -> u32 {
+    player.carrying.get(&stash.ingredient).map_or(0, |units| units.len() as u32)
+}
+
+fn describe_choice(stash: &TeaStash, player: &Player, amount: i32) -> String {
+    let choice = if amount > 0 {
+        format!("< Take {} tsp >", amount)
+    } else if amount < 0 {
+        format!("< Put back {} tsp >", -amount)
+    } else {
+        "< No change >".to_owned()
+    };
+    let freshness = match stash.freshness() {
+        Some(freshness) => format!(" ({})", freshness.adjective()),
+        None => String::new(),
+    };
+    format!(
+        "{:?}\n\nYou are carrying {} tsp; {} remaining in the jar{}.\n\n{}",
+        stash.ingredient,
+        carried(stash, player),
+        stash.amount(),
+        freshness,
+        choice,
+    )
+}
+
+fn run_picker(
+    mut picker: Query<&mut StashPicker>,
+    mut text_box: Query<&mut Text, With<PickerText>>,
+    mut stashes: Query<&mut TeaStash>,
+    mut players: Query<&mut Player>,
+    keys: Res<Input<KeyCode>>,
+    mut game_state: ResMut<State<GameState>>,
+    mut status_events: EventWriter<StatusEvent>,
+) {
+    let mut picker = picker.single_mut();
+    let mut text_box = text_box.single_mut();
+    let mut stash = stashes.get_mut(picker.stash).unwrap();
+    let mut player = players.get_mut(picker.player).unwrap();
+
+    let min = -(carried(&stash, &player) as i32);
+    let max = stash.amount() as i32;
+    if keys.just_released(KeyCode::Left) {
+        picker.amount = (picker.amount - 1).max(min);
+    }
+    if keys.just_released(KeyCode::Right) {
+        picker.amount = (picker.amount + 1).min(max);
+    }
+    text_box.sections[0].value = describe_choice(&stash, &player, picker.amount);
+
+    if keys.just_released(KeyCode::Back) {
+        game_state.set(GameState::InGame).unwrap();
+        return;
+    }
+
+    if !keys.just_released(KeyCode::Space) {
+        return;
+    }
+
+    let ingredient = stash.ingredient;
+    let message = if picker.amount > 0 {
+        // The stalest teaspoons are used up first.
+        let amount = picker.amount as usize;
+        let units = stash.units.drain(..amount).collect::<Vec<_>>();
+        player.carrying.entry(ingredient).or_default().extend(units);
+        if ingredient.is_additive() && !player.additives.contains(&ingredient) {
+            player.additives.push(ingredient);
+        }
+        format!("You measure out {} tsp of {:?} ({} remaining)", amount, ingredient, stash.amount())
+    } else if picker.amount < 0 {
+        let amount = (-picker.amount) as usize;
+        let carrying = player.carrying.get_mut(&ingredient).unwrap();
+        let units = carrying.split_off(carrying.len() - amount);
+        if carrying.is_empty() {
+            player.carrying.remove(&ingredient);
+            player.additives.retain(|additive| *additive != ingredient);
+        }
+        stash.units.extend(units);
+        stash.units.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        format!("You put back {} tsp of {:?} ({} remaining)", amount, ingredient, stash.amount())
+    } else {
+        game_state.set(GameState::InGame).unwrap();
+        return;
+    };
+    status_events.send(StatusEvent::timed_message(
+        picker.player,
+        message,
+        DEFAULT_EXPIRY,
+    ));
+    game_state.set(GameState::InGame).unwrap();
+}
+
+fn exit_picker(
+    picker: Query<(Entity, &StashPicker)>,
+    mut commands: Commands,
+) {
+    let (entity, picker) = picker.single();
+    commands.entity(picker.box_entity).despawn_recursive();
+    commands.entity(entity).despawn();
+}
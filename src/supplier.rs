@@ -0,0 +1,335 @@
+use bevy::prelude::*;
+use crate::GameState;
+use crate::dialog::{Panel, spawn_panel};
+use crate::geom::HasSize;
+use crate::interaction::{Interactable, PlayerInteracted};
+use crate::message_line::{DEFAULT_EXPIRY, StatusEvent};
+use crate::money::Money;
+use crate::movable::Movable;
+use crate::tea::{Ingredient, TeaStash, teastash_components};
+use std::time::Duration;
+use strum::IntoEnumIterator;
+
+pub struct SupplierPlugin;
+
+impl Plugin for SupplierPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Deliveries>()
+            .add_system(interact_with_order_board)
+            .add_system(receive_deliveries)
+            .add_system_set(
+                SystemSet::on_update(GameState::Ordering)
+                    .with_system(run_order_form)
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Ordering)
+                    .with_system(exit_order_form)
+            );
+    }
+}
+
+const DELIVERY_AMOUNT: u32 = 20;
+const DELIVERY_TIME: Duration = Duration::from_secs(60);
+// What the supplier charges for a delivery of each ingredient.
+fn delivery_price(ingredient: Ingredient) -> u32 {
+    match ingredient {
+        Ingredient::Sugar | Ingredient::BrownSugar => 4,
+        Ingredient::Milk | Ingredient::Cream | Ingredient::Lemon | Ingredient::Honey => 6,
+        Ingredient::BlackTea | Ingredient::Chai | Ingredient::Rooibos | Ingredient::Ceylon => 8,
+        Ingredient::GreenTea |
+        Ingredient::OolongTea |
+        Ingredient::MintLeaf |
+        Ingredient::CitrusPeel => 10,
+        Ingredient::Matcha => 14,
+    }
+}
+
+// A jar from the interior tileset, for newly stocked ingredients.
+const JAR_TILE: usize = 311;
+
+#[derive(Component)]
+pub struct OrderBoard;
+
+// A space for a stash of an ingredient that isn't stocked yet.
+#[derive(Component)]
+pub struct EmptyShelf;
+
+pub struct Delivery {
+    pub ingredient: Ingredient,
+    pub amount: u32,
+    timer: Timer,
+}
+
+#[derive(Resource, Default)]
+pub struct Deliveries(pub Vec<Delivery>);
+
+#[derive(Component)]
+struct OrderFormText;
+
+#[derive(Component)]
+struct OrderForm {
+    selected: usize,
+    player: Entity,
+    box_entity: Entity,
+}
+
+fn interact_with_order_board(
+    mut player_interacted_events: EventReader<PlayerInteracted>,
+    board: Query<Entity, With<OrderBoard>>,
+    mut game_state: ResMut<State<GameState>>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    for event in player_interacted_events.iter() {
+        if board.get(event.interacted_entity).is_err() {
+            continue;
+        }
+        game_state.set(GameState::Ordering).unwrap();
+        show_order_form(event.player_entity, &mut commands, &asset_server);
+        return;
+    }
+}
+
+fn show_order_form(
+    player: Entity,
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+) {
+    let id = spawn_panel(commands, asset_server, OrderFormText, Panel {
+        hint: "Left/right to choose, space to order, backspace to close.",
+        background: Color::rgb(0.6, 0.5, 0.3),
+        hint_color: Color::rgb(1.0, 0.9, 0.8),
+        ..default()
+    });
+
+    commands.spawn(OrderForm {
+        selected: 0,
+        player,
+        box_entity: id,
+    });
+}
+
+enum OrderStatus {
+    Stocked(u32),
+    OnOrder(Duration),
+    Unstocked,
+    NoRoom,
+}
+
+fn order_status(
+    ingredient: Ingredient,
+    stashes: &Query<&TeaStash>,
+    deliveries: &Deliveries,
+    empty_shelves: usize,
+) -> OrderStatus {
+    if let Some(delivery) = deliveries.0.iter().find(|d| d.ingredient == ingredient) {
+        return OrderStatus::OnOrder(delivery.timer.remaining());
+    }
+    if let Some(stash) = stashes.iter().find(|stash| stash.ingredient == ingredient) {
+        return OrderStatus::Stocked(stash.amount());
+    }
+    // Shelves are claimed by pending deliveries of new ingredients as well.
+    let claimed = deliveries.0
+        .iter()
+        .filter(|d| stashes.iter().all(|stash| stash.ingredient != d.ingredient))
+        .count();
+    if claimed < empty_shelves {
+        OrderStatus::Unstocked
+    } else {
+        OrderStatus::NoRoom
+    }
+}
+
+fn describe_order(ingredient: Ingredient, status: &OrderStatus, coins: u32) -> String {
+    let price = delivery_price(ingredient);
+    let status = match status {
+        OrderStatus::Stocked(amount) => format!(
+            "In stock: {} tsp. Order another {} tsp for {} coins?",
+            amount,
+            DELIVERY_AMOUNT,
+            price,
+        ),
+        OrderStatus::OnOrder(remaining) if remaining.is_zero() =>
+            "Delivered, but waiting for room on the shelves.".to_owned(),
+        OrderStatus::OnOrder(remaining) =>
+            format!("On order; arriving in {}s.", remaining.as_secs()),
+        OrderStatus::Unstocked => format!(
+            "Not stocked. Order {} tsp for an empty shelf, for {} coins?",
+            DELIVERY_AMOUNT,
+            price,
+        ),
+        OrderStatus::NoRoom =>
+            "Not stocked, and there's no room on the shelves.".to_owned(),
+    };
+    format!(
+        "Order from the supplier (you have {} coins)\n\n< {:?} >\n{}",
+        coins,
+        ingredient,
+        status,
+    )
+}
+
+fn run_order_form(
+    mut form: Query<&mut OrderForm>,
+    mut text_box: Query<&mut Text, With<OrderFormText>>,
+    stashes: Query<&TeaStash>,
+    empty_shelves: Query<&EmptyShelf>,
+    mut deliveries: ResMut<Deliveries>,
+    mut money: ResMut<Money>,
+    keys: Res<Input<KeyCode>>,
+    mut game_state: ResMut<State<GameState>>,
+    mut status_events: EventWriter<StatusEvent>,
+) {
+    let mut form = form.single_mut();
+    let mut text_box = text_box.single_mut();
+    let ingredients = Ingredient::iter().collect::<Vec<_>>();
+
+    if keys.just_released(KeyCode::Left) {
+        form.selected = (form.selected + ingredients.len() - 1) % ingredients.len();
+    }
+    if keys.just_released(KeyCode::Right) {
+        form.selected = (form.selected + 1) % ingredients.len();
+    }
+
+    let ingredient = ingredients[form.selected];
+    let status = order_status(ingredient, &stashes, &deliveries, empty_shelves.iter().count());
+
+    if keys.just_released(KeyCode::Space) {
+        match status {
+            OrderStatus::Stocked(_) | OrderStatus::Unstocked => {
+                let price = delivery_price(ingredient);
+                let message = if money.spend(price) {
+                    deliveries.0.push(Delivery {
+                        ingredient,
+                        amount: DELIVERY_AMOUNT,
+                        timer: Timer::new(DELIVERY_TIME, TimerMode::Once),
+                    });
+                    format!(
+                        "You order {} tsp of {:?} for {} coins.",
+                        DELIVERY_AMOUNT,
+                        ingredient,
+                        price,
+                    )
+                } else {
+                    format!("You can't afford {} coins for {:?}.", price, ingredient)
+                };
+                status_events.send(StatusEvent::timed_message(
+                    form.player,
+                    message,
+                    DEFAULT_EXPIRY,
+                ));
+            }
+            OrderStatus::OnOrder(_) | OrderStatus::NoRoom => (),
+        }
+    }
+
+    let status = order_status(ingredient, &stashes, &deliveries, empty_shelves.iter().count());
+    text_box.sections[0].value = describe_order(ingredient, &status, money.coins);
+
+    if keys.just_released(KeyCode::Back) {
+        game_state.set(GameState::InGame).unwrap();
+    }
+}
+
+fn exit_order_form(
+    form: Query<(Entity, &OrderForm)>,
+    mut commands: Commands,
+) {
+    let (entity, form) = form.single();
+    commands.entity(form.box_entity).despawn_recursive();
+    commands.entity(entity).despawn();
+}
+
+fn receive_deliveries(
+    mut deliveries: ResMut<Deliveries>,
+    mut stashes: Query<(Entity, &mut TeaStash)>,
+    mut empty_shelves: Query<(Entity, &mut Visibility), With<EmptyShelf>>,
+    mut status_events: EventWriter<StatusEvent>,
+    mut commands: Commands,
+    time: Res<Time>,
+) {
+    for delivery in deliveries.0.iter_mut() {
+        delivery.timer.tick(time.delta());
+    }
+
+    // Deliveries of the same ingredient that arrive together share a stash.
+    let mut arrived: Vec<(Ingredient, u32)> = vec![];
+    for delivery in deliveries.0.iter().filter(|d| d.timer.finished()) {
+        match arrived.iter_mut().find(|(ingredient, _)| *ingredient == delivery.ingredient) {
+            Some((_, amount)) => *amount += delivery.amount,
+            None => arrived.push((delivery.ingredient, delivery.amount)),
+        }
+    }
+
+    let mut shelves = empty_shelves.iter_mut();
+    for (ingredient, amount) in arrived {
+        let existing = stashes
+            .iter_mut()
+            .find(|(_, stash)| stash.ingredient == ingredient);
+        let stash_entity = match existing {
+            Some((entity, mut stash)) => {
+                stash.units.extend(vec![1.; amount as usize]);
+                entity
+            }
+            None => match shelves.next() {
+                Some((shelf, mut visibility)) => {
+                    visibility.is_visible = true;
+                    commands.entity(shelf)
+                        .remove::<EmptyShelf>()
+                        .insert(teastash_components(ingredient, amount));
+                    shelf
+                }
+                // Keep the delivery waiting rather than losing the stock.
+                None => continue,
+            },
+        };
+        deliveries.0.retain(|d| d.ingredient != ingredient || !d.timer.finished());
+        status_events.send(StatusEvent::timed_message(
+            stash_entity,
+            format!("A delivery of {} tsp of {:?} has arrived.", amount, ingredient),
+            DEFAULT_EXPIRY,
+        ));
+    }
+}
+
+pub fn spawn_order_board(
+    commands: &mut Commands,
+    movable: Movable,
+    sized: HasSize,
+    transform: Transform,
+) {
+    commands.spawn((
+        OrderBoard,
+        Interactable {
+            message: "Press X to order ingredients".to_owned(),
+            ..default()
+        },
+        movable,
+        sized,
+        transform,
+    ));
+}
+
+pub fn spawn_empty_shelf(
+    commands: &mut Commands,
+    movable: Movable,
+    sized: HasSize,
+    mut transform: Transform,
+    atlas: Handle<TextureAtlas>,
+) {
+    transform.translation.z = 0.5;
+    commands.spawn((
+        EmptyShelf,
+        movable,
+        sized,
+        // The jar only appears once a delivery fills it.
+        SpriteSheetBundle {
+            texture_atlas: atlas,
+            sprite: TextureAtlasSprite::new(JAR_TILE),
+            transform,
+            visibility: Visibility { is_visible: false },
+            ..default()
+        },
+    ));
+}
@@ -1,16 +1,20 @@
 use bevy::prelude::*;
 use bevy::utils::Instant;
+use crate::GameState;
 use crate::entity::Item;
+use crate::flavour::Flavour;
 use crate::geom::{TILE_SIZE, MapSize, MapPos, HasSize, map_to_screen};
-use crate::interaction::{Interactable, PlayerInteracted, AutoPickUp};
+use crate::interaction::{Interactable, PlayerInteracted, AutoPickUp, despawn_held_entity};
 use crate::map::Map;
 use crate::message_line::{DEFAULT_EXPIRY, StatusEvent};
 use crate::movable::Movable;
-use crate::player::Player;
+use crate::player::{Holding, Player};
+use crate::stash::show_stash_picker;
 use rand::Rng;
 use rand_derive2::RandGen;
 use std::collections::HashMap;
 use std::time::Duration;
+use strum::{EnumIter, EnumString};
 
 pub struct TeaPlugin;
 
@@ -18,44 +22,153 @@ impl Plugin for TeaPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_event::<SpawnTeapotEvent>()
+            .add_event::<SpawnCupEvent>()
             .add_startup_system(init_texture)
             .add_system(spawn_teapot)
+            .add_system(spawn_cup)
             .add_system(interact_with_stash)
             .add_system(interact_with_cupboards)
+            .add_system(interact_with_cup_shelf)
+            .add_system(pour_tea)
             .add_system(interact_with_kettles)
-            .add_system(use_dirty_teapot_with_sink);
+            .add_system(heat_kettles)
+            .add_system(cool_brewed_tea)
+            .add_system(spoil_ingredients)
+            .add_system(use_dirty_dishes_with_sink)
+            .add_system(wash_dishes)
+            .add_system(tint_dirty_dishes)
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(cover_held_teapot)
+                    .with_system(inspect_held_teapot)
+            );
     }
 }
 
 #[derive(Component)]
 pub struct Dirty;
 
-#[derive(Component)]
-pub struct Sink;
+const WASH_TIME: Duration = Duration::from_secs(8);
+const DIRTY_TINT: Color = Color::rgb(0.6, 0.5, 0.4);
+
+// The amount of water poured into each cup.
+pub const CUP_WATER: u32 = 25;
+// Cups share the teapot sprite, in a paler glaze.
+const CUP_TINT: Color = Color::rgb(0.95, 0.95, 1.0);
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Dish {
+    Teapot,
+    Cup,
+}
+
+#[derive(Component, Default)]
+pub struct Sink {
+    // Dishes waiting to be washed; only the first one is being worked on.
+    pub washing: Vec<(Dish, Timer)>,
+}
+
+pub const ROOM_TEMPERATURE: f32 = 20.;
+pub const BOILING_TEMPERATURE: f32 = 100.;
+// Degrees per second.
+const KETTLE_HEATING_RATE: f32 = 4.;
+const KETTLE_COOLING_RATE: f32 = 0.5;
+// Fraction of the difference from room temperature lost per second.
+const TEAPOT_COOLING_RATE: f32 = 0.01;
+const HELD_TEAPOT_COOLING_RATE: f32 = 0.004;
+const COVERED_COOLING_FACTOR: f32 = 0.5;
+const LUKEWARM_TEMPERATURE: f32 = 55.;
+const COLD_TEMPERATURE: f32 = 35.;
+// Ingredients below these levels of freshness are noticeably past their best.
+const STALE_FRESHNESS: f32 = 0.5;
+const SPOILED_FRESHNESS: f32 = 0.15;
 
 #[derive(Component)]
-pub struct Kettle;
+pub struct Kettle {
+    pub on: bool,
+    pub temperature: f32,
+    // A teapot that the player has been warned will be emptied if they refill it.
+    pub pending_refill: Option<Entity>,
+}
+
+impl Default for Kettle {
+    fn default() -> Self {
+        Self {
+            on: false,
+            temperature: ROOM_TEMPERATURE,
+            pending_refill: None,
+        }
+    }
+}
 
 #[derive(Component)]
 pub struct Cupboard {
     pub teapots: u32,
+    pub cups: u32,
+}
+
+// A single serving, poured from a teapot.
+#[derive(Component, Default)]
+pub struct Cup {
+    pub tea: Option<TeaPot>,
 }
 
+// Where clean cups are kept, alongside the teapots in the cupboard.
+#[derive(Component)]
+pub struct CupShelf;
+
 #[derive(Component)]
 pub struct TeaStash {
     pub ingredient: Ingredient,
-    pub amount: u32,
+    // The freshness of each teaspoon in the stash, stalest first.
+    pub units: Vec<f32>,
+}
+
+impl TeaStash {
+    pub fn amount(&self) -> u32 {
+        self.units.len() as u32
+    }
+
+    /// The freshness of the next teaspoon to be measured out.
+    pub fn freshness(&self) -> Option<Freshness> {
+        self.units.first().map(|freshness| Freshness::from_value(*freshness))
+    }
 }
 
-#[derive(Component, Default, Clone)]
+#[derive(Component, Clone)]
 pub struct TeaPot {
     pub ingredients: HashMap<Ingredient, u32>,
     pub steeped_at: Option<Instant>,
     pub steeped_for: Option<Duration>,
     pub water: u32,
+    pub water_temperature: f32,
+    // The current temperature of the brewed tea.
+    pub temperature: f32,
+    // A pot with its lid on keeps warm for longer.
+    pub covered: bool,
+    // The freshness of the stalest ingredient that went into the pot.
+    pub ingredient_freshness: f32,
+    // Additives stirred into the tea after it was steeped, in the order they were added.
+    pub additions: Vec<Ingredient>,
 }
 
-#[derive(Hash, RandGen, Copy, Clone, PartialEq, Eq, Debug)]
+impl Default for TeaPot {
+    fn default() -> Self {
+        Self {
+            ingredients: HashMap::new(),
+            steeped_at: None,
+            steeped_for: None,
+            water: 0,
+            water_temperature: 0.,
+            temperature: 0.,
+            covered: false,
+            ingredient_freshness: 1.,
+            additions: vec![],
+        }
+    }
+}
+
+#[derive(Hash, RandGen, EnumIter, EnumString, Copy, Clone, PartialEq, Eq, Debug)]
 pub enum Ingredient {
     BlackTea,
     OolongTea,
@@ -74,78 +187,495 @@ pub enum Ingredient {
     BrownSugar,
 }
 
-fn use_dirty_teapot_with_sink(
+impl Ingredient {
+    /// The range of steeping times that produce a good brew, or None for
+    /// ingredients that are unaffected by how long they sit in the water.
+    pub fn steep_window(&self) -> Option<(Duration, Duration)> {
+        let (min, max) = match self {
+            Ingredient::BlackTea => (20, 40),
+            Ingredient::OolongTea => (15, 35),
+            Ingredient::Chai => (25, 50),
+            Ingredient::Rooibos => (25, 60),
+            Ingredient::GreenTea => (10, 25),
+            Ingredient::Matcha => (5, 15),
+            Ingredient::Ceylon => (20, 40),
+            Ingredient::MintLeaf => (15, 45),
+            Ingredient::CitrusPeel => (10, 40),
+            Ingredient::Sugar |
+            Ingredient::Honey |
+            Ingredient::Milk |
+            Ingredient::Lemon |
+            Ingredient::Cream |
+            Ingredient::BrownSugar => return None,
+        };
+        Some((Duration::from_secs(min), Duration::from_secs(max)))
+    }
+
+    pub fn flavour(&self) -> Flavour {
+        //                              sweet bitter floral citrus creamy earthy
+        match self {
+            Ingredient::BlackTea => Flavour::new(0.0, 0.6, 0.0, 0.0, 0.0, 0.5),
+            Ingredient::OolongTea => Flavour::new(0.1, 0.3, 0.5, 0.0, 0.0, 0.3),
+            Ingredient::Chai => Flavour::new(0.2, 0.2, 0.1, 0.0, 0.0, 0.6),
+            Ingredient::CitrusPeel => Flavour::new(0.0, 0.2, 0.1, 0.9, 0.0, 0.0),
+            Ingredient::MintLeaf => Flavour::new(0.1, 0.0, 0.5, 0.2, 0.0, 0.1),
+            Ingredient::Sugar => Flavour::new(1.0, 0.0, 0.0, 0.0, 0.0, 0.0),
+            Ingredient::Honey => Flavour::new(0.8, 0.0, 0.3, 0.0, 0.0, 0.0),
+            Ingredient::Milk => Flavour::new(0.1, 0.0, 0.0, 0.0, 0.8, 0.0),
+            Ingredient::Lemon => Flavour::new(0.0, 0.1, 0.0, 1.0, 0.0, 0.0),
+            Ingredient::Rooibos => Flavour::new(0.3, 0.0, 0.1, 0.0, 0.0, 0.5),
+            Ingredient::GreenTea => Flavour::new(0.0, 0.4, 0.3, 0.0, 0.0, 0.3),
+            Ingredient::Matcha => Flavour::new(0.0, 0.5, 0.0, 0.0, 0.1, 0.6),
+            Ingredient::Ceylon => Flavour::new(0.0, 0.5, 0.0, 0.2, 0.0, 0.4),
+            Ingredient::Cream => Flavour::new(0.2, 0.0, 0.0, 0.0, 1.0, 0.0),
+            Ingredient::BrownSugar => Flavour::new(0.9, 0.0, 0.0, 0.0, 0.0, 0.2),
+        }
+    }
+
+    /// The range of water temperatures that suit this ingredient, or None for
+    /// ingredients that don't care.
+    pub fn ideal_temperature(&self) -> Option<(f32, f32)> {
+        Some(match self {
+            Ingredient::BlackTea => (90., 100.),
+            Ingredient::OolongTea => (80., 95.),
+            Ingredient::Chai => (90., 100.),
+            Ingredient::Rooibos => (90., 100.),
+            Ingredient::GreenTea => (70., 85.),
+            Ingredient::Matcha => (70., 80.),
+            Ingredient::Ceylon => (90., 100.),
+            Ingredient::MintLeaf => (85., 100.),
+            Ingredient::CitrusPeel => (85., 100.),
+            Ingredient::Sugar |
+            Ingredient::Honey |
+            Ingredient::Milk |
+            Ingredient::Lemon |
+            Ingredient::Cream |
+            Ingredient::BrownSugar => return None,
+        })
+    }
+
+    /// Additives are stirred into a tea once it has steeped, rather than
+    /// being steeped themselves.
+    pub fn is_additive(&self) -> bool {
+        matches!(
+            self,
+            Ingredient::Milk |
+            Ingredient::Sugar |
+            Ingredient::Honey |
+            Ingredient::Cream |
+            Ingredient::Lemon |
+            Ingredient::BrownSugar
+        )
+    }
+
+    /// How long this ingredient takes to go from fresh to spoiled, or None
+    /// for ingredients that keep indefinitely.
+    pub fn shelf_life(&self) -> Option<Duration> {
+        let secs = match self {
+            Ingredient::Milk => 180,
+            Ingredient::Cream => 150,
+            Ingredient::Lemon => 600,
+            Ingredient::MintLeaf => 900,
+            Ingredient::CitrusPeel => 1200,
+            Ingredient::GreenTea | Ingredient::Matcha => 2400,
+            Ingredient::BlackTea |
+            Ingredient::OolongTea |
+            Ingredient::Chai |
+            Ingredient::Rooibos |
+            Ingredient::Ceylon => 3600,
+            Ingredient::Sugar |
+            Ingredient::Honey |
+            Ingredient::BrownSugar => return None,
+        };
+        Some(Duration::from_secs(secs))
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Freshness {
+    Fresh,
+    Stale,
+    Spoiled,
+}
+
+impl Freshness {
+    pub fn from_value(freshness: f32) -> Freshness {
+        if freshness < SPOILED_FRESHNESS {
+            Freshness::Spoiled
+        } else if freshness < STALE_FRESHNESS {
+            Freshness::Stale
+        } else {
+            Freshness::Fresh
+        }
+    }
+
+    pub fn adjective(&self) -> &'static str {
+        match self {
+            Freshness::Fresh => "fresh",
+            Freshness::Stale => "stale",
+            Freshness::Spoiled => "spoiled",
+        }
+    }
+}
+
+const OVERSTEEPED_BITTERNESS: f32 = 1.5;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum WaterQuality {
+    TooCool,
+    Ideal,
+    TooHot,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ServingTemperature {
+    Hot,
+    Lukewarm,
+    Cold,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum BrewQuality {
+    UnderSteeped,
+    Perfect,
+    Bitter,
+}
+
+impl TeaPot {
+    /// The combined flavour of everything in the pot. Tea that has been left to
+    /// steep too long picks up extra bitterness.
+    pub fn flavour(&self) -> Flavour {
+        let mut flavour = Flavour::default();
+        for (ingredient, amount) in &self.ingredients {
+            flavour += ingredient.flavour().scaled(*amount as f32);
+        }
+        if self.brew_quality() == Some(BrewQuality::Bitter) {
+            flavour.bitter += OVERSTEEPED_BITTERNESS;
+        }
+        flavour
+    }
+
+    /// The window in which every steepable ingredient in the pot is brewed
+    /// well enough. When the individual windows don't overlap, the window
+    /// collapses to the point where the weakest ingredient is ready.
+    pub fn steep_window(&self) -> Option<(Duration, Duration)> {
+        let mut window: Option<(Duration, Duration)> = None;
+        for ingredient in self.ingredients.keys() {
+            let (min, max) = match ingredient.steep_window() {
+                Some(window) => window,
+                None => continue,
+            };
+            window = Some(match window {
+                Some((lower, upper)) => (lower.max(min), upper.min(max)),
+                None => (min, max),
+            });
+        }
+        window.map(|(lower, upper)| (lower, upper.max(lower)))
+    }
+
+    pub fn brew_quality(&self) -> Option<BrewQuality> {
+        let steeped_for = self.steeped_for?;
+        let (min, max) = match self.steep_window() {
+            Some(window) => window,
+            None => return Some(BrewQuality::Perfect),
+        };
+        Some(if steeped_for < min {
+            BrewQuality::UnderSteeped
+        } else if steeped_for > max {
+            BrewQuality::Bitter
+        } else {
+            BrewQuality::Perfect
+        })
+    }
+
+    pub fn serving_temperature(&self) -> Option<ServingTemperature> {
+        if self.water == 0 {
+            return None;
+        }
+        Some(if self.temperature < COLD_TEMPERATURE {
+            ServingTemperature::Cold
+        } else if self.temperature < LUKEWARM_TEMPERATURE {
+            ServingTemperature::Lukewarm
+        } else {
+            ServingTemperature::Hot
+        })
+    }
+
+    /// How well the temperature of the water poured into the pot suited the
+    /// ingredients that were steeped in it.
+    pub fn water_quality(&self) -> Option<WaterQuality> {
+        if self.water == 0 {
+            return None;
+        }
+        let mut ideal: Option<(f32, f32)> = None;
+        for ingredient in self.ingredients.keys() {
+            let (min, max) = match ingredient.ideal_temperature() {
+                Some(range) => range,
+                None => continue,
+            };
+            ideal = Some(match ideal {
+                Some((lower, upper)) => (lower.max(min), upper.min(max)),
+                None => (min, max),
+            });
+        }
+        let (min, max) = match ideal {
+            Some((lower, upper)) => (lower, upper.max(lower)),
+            None => return Some(WaterQuality::Ideal),
+        };
+        Some(if self.water_temperature < min {
+            WaterQuality::TooCool
+        } else if self.water_temperature > max {
+            WaterQuality::TooHot
+        } else {
+            WaterQuality::Ideal
+        })
+    }
+
+    pub fn freshness(&self) -> Freshness {
+        Freshness::from_value(self.ingredient_freshness)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ingredients.is_empty() && self.water == 0
+    }
+
+    /// The steeped ingredients, followed by the additives in the order they
+    /// were stirred in.
+    pub fn describe_ingredients(&self) -> String {
+        let mut steeped = self.ingredients
+            .iter()
+            .filter(|(ingredient, _)| !ingredient.is_additive())
+            .collect::<Vec<_>>();
+        steeped.sort_by_key(|(ingredient, _)| format!("{:?}", ingredient));
+        let mut parts = steeped
+            .into_iter()
+            .map(|(ingredient, amount)| format!("{} tsp of {:?}", amount, ingredient))
+            .collect::<Vec<_>>();
+        for ingredient in &self.additions {
+            parts.push(format!("then {} tsp of {:?}", self.ingredients[ingredient], ingredient));
+        }
+        parts.join(", ")
+    }
+
+    pub fn describe_contents(&self, now: Instant) -> String {
+        if self.is_empty() {
+            return "The teapot is empty.".to_owned();
+        }
+        let mut description = format!("The teapot holds {}", self.describe_ingredients());
+        if let Some(steeped_at) = self.steeped_at {
+            description += &format!(
+                ", steeped for {}s in {} water",
+                (now - steeped_at).as_secs(),
+                describe_water(self.water_temperature),
+            );
+        }
+        description += &format!(
+            ". It's {:.0}°C, with enough for {} cups.",
+            self.temperature,
+            self.water / CUP_WATER,
+        );
+        description
+    }
+}
+
+fn use_dirty_dishes_with_sink(
     mut events: EventReader<PlayerInteracted>,
-    sink: Query<Entity, With<Sink>>,
-    teapots: Query<Entity, With<TeaPot>>,
-    mut cupboards: Query<&mut Cupboard>,
+    mut sink: Query<(Entity, &mut Sink)>,
+    mut teapots: Query<(&mut TeaPot, Option<&Dirty>)>,
+    cups: Query<(&Cup, Option<&Dirty>)>,
+    mut status_events: EventWriter<StatusEvent>,
     mut commands: Commands,
 ) {
     for event in events.iter() {
-        let sink = sink.single();
-        if event.interacted_entity != sink {
+        let (sink_entity, mut sink) = sink.single_mut();
+        if event.interacted_entity != sink_entity {
             continue;
         }
         let held = match event.held_entity {
             Some(entity) => entity,
             None => continue,
         };
-        if teapots.get(held).is_ok() {
+        let (dish, needs_washing) = if let Ok((mut teapot, dirty)) = teapots.get_mut(held) {
+            // A clean teapot that was filled can be poured out and used again.
+            if dirty.is_none() && !teapot.is_empty() {
+                *teapot = TeaPot::default();
+                status_events.send(StatusEvent::timed_message(
+                    event.player_entity,
+                    "You pour the teapot's contents down the sink.".to_owned(),
+                    DEFAULT_EXPIRY,
+                ));
+                continue;
+            }
+            (Dish::Teapot, dirty.is_some())
+        } else if let Ok((cup, dirty)) = cups.get(held) {
+            (Dish::Cup, dirty.is_some() || cup.tea.is_some())
+        } else {
+            continue;
+        };
+        let name = match dish {
+            Dish::Teapot => "teapot",
+            Dish::Cup => "cup",
+        };
+        let message = if needs_washing {
+            sink.washing.push((dish, Timer::new(WASH_TIME, TimerMode::Once)));
+            despawn_held_entity(&mut commands, event.player_entity, held);
+            format!("You leave the {} in the sink to wash ({} waiting).", name, sink.washing.len())
+        } else {
+            format!("That {} is already clean. It belongs in the cupboard.", name)
+        };
+        status_events.send(StatusEvent::timed_message(
+            event.player_entity,
+            message,
+            DEFAULT_EXPIRY,
+        ));
+    }
+}
+
+fn sink_message(sink: &Sink) -> String {
+    if sink.washing.is_empty() {
+        "Press X to wash dirty dishes.".to_owned()
+    } else {
+        format!("Washing up ({} waiting). Press X to wash dirty dishes.", sink.washing.len())
+    }
+}
+
+fn wash_dishes(
+    mut sinks: Query<(&mut Sink, &mut Interactable)>,
+    mut cupboards: Query<&mut Cupboard>,
+    time: Res<Time>,
+) {
+    for (mut sink, mut interactable) in &mut sinks {
+        let finished = match sink.washing.first_mut() {
+            Some((_, timer)) => {
+                timer.tick(time.delta());
+                timer.finished()
+            }
+            None => continue,
+        };
+        if finished {
+            let (dish, _) = sink.washing.remove(0);
             let mut cupboard = cupboards.single_mut();
-            cupboard.teapots += 1;
-            commands.entity(held).despawn();
+            match dish {
+                Dish::Teapot => cupboard.teapots += 1,
+                Dish::Cup => cupboard.cups += 1,
+            }
+        }
+        let message = sink_message(&sink);
+        if interactable.message != message {
+            interactable.message = message;
         }
     }
 }
 
+fn tint_dirty_dishes(
+    mut dishes: Query<&mut Sprite, Added<Dirty>>,
+) {
+    for mut sprite in &mut dishes {
+        sprite.color = DIRTY_TINT;
+    }
+}
+
 fn interact_with_stash(
-    mut q: Query<&mut Player>,
+    q: Query<&Player>,
     mut player_interacted_events: EventReader<PlayerInteracted>,
-    mut stash: Query<&mut TeaStash>,
+    stash: Query<&TeaStash>,
     mut status_events: EventWriter<StatusEvent>,
+    mut game_state: ResMut<State<GameState>>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
 ) {
     for event in player_interacted_events.iter() {
-        let mut player = q.get_mut(event.player_entity).unwrap();
-        let mut stash = match stash.get_mut(event.interacted_entity) {
+        let player = q.get(event.player_entity).unwrap();
+        let stash = match stash.get(event.interacted_entity) {
             Ok(result) => result,
             Err(_) => continue,
         };
 
-        if stash.amount > 0 {
-            stash.amount -= 1;
-            let amount = player.carrying.entry(stash.ingredient).or_insert(0);
-            *amount += 1;
+        if stash.units.is_empty() && !player.carrying.contains_key(&stash.ingredient) {
             status_events.send(StatusEvent::timed_message(
                 event.player_entity,
-                format!("You take a little {:?} ({} remaining)", stash.ingredient, stash.amount),
+                format!("There's no {:?} left.", stash.ingredient),
                 DEFAULT_EXPIRY,
             ));
+            continue;
         }
+
+        game_state.set(GameState::Picking).unwrap();
+        show_stash_picker(
+            event.interacted_entity,
+            event.player_entity,
+            stash,
+            player,
+            &mut commands,
+            &asset_server,
+        );
+        return;
     }
 }
 
+fn inspect_held_teapot(
+    keys: Res<Input<KeyCode>>,
+    player: Query<(Entity, &Holding), With<Player>>,
+    teapots: Query<&TeaPot>,
+    mut status_events: EventWriter<StatusEvent>,
+    time: Res<Time>,
+) {
+    if !keys.just_released(KeyCode::I) {
+        return;
+    }
+    let (player_entity, holding) = match player.get_single() {
+        Ok(result) => result,
+        Err(_) => return,
+    };
+    let teapot = match teapots.get(holding.entity) {
+        Ok(teapot) => teapot,
+        Err(_) => return,
+    };
+    status_events.send(StatusEvent::timed_message(
+        player_entity,
+        teapot.describe_contents(time.last_update().unwrap()),
+        DEFAULT_EXPIRY,
+    ));
+}
+
 fn interact_with_cupboards(
     mut player_interacted_events: EventReader<PlayerInteracted>,
     mut cupboards: Query<&mut Cupboard>,
     mut status_events: EventWriter<StatusEvent>,
-    teapot: Query<&TeaPot, With<Player>>,
+    teapots: Query<(&TeaPot, Option<&Dirty>)>,
+    cups: Query<(), With<Cup>>,
     mut teapot_spawner: EventWriter<SpawnTeapotEvent>,
+    mut commands: Commands,
 ) {
     for event in player_interacted_events.iter() {
         let mut cupboard = match cupboards.get_mut(event.interacted_entity) {
             Ok(result) => result,
             Err(_) => continue,
         };
-        let message = if cupboard.teapots > 0 {
-            if teapot.is_empty() {
+        let message = match event.held_entity {
+            Some(held) => {
+                if let Ok((teapot, dirty)) = teapots.get(held) {
+                    if dirty.is_some() || teapot.water > 0 {
+                        "That teapot needs to be washed before it goes back.".to_string()
+                    } else {
+                        cupboard.teapots += 1;
+                        despawn_held_entity(&mut commands, event.player_entity, held);
+                        format!("You put the teapot away ({} left).", cupboard.teapots)
+                    }
+                } else if cups.get(held).is_ok() {
+                    "Cups go on the shelf next to the cupboard.".to_string()
+                } else {
+                    "Your hands are full.".to_string()
+                }
+            }
+            None if cupboard.teapots > 0 => {
                 cupboard.teapots -= 1;
                 teapot_spawner.send(SpawnTeapotEvent::into_holding());
                 format!("You take a teapot ({} left).", cupboard.teapots)
-            } else {
-                "You're already carrying a teapot.".to_string()
             }
-        } else {
-            "No teapots remaining.".to_string()
+            None => {
+                "No teapots remaining. Wash some dirty ones at the sink.".to_string()
+            }
         };
         status_events.send(StatusEvent::timed_message(
             event.player_entity,
@@ -155,33 +685,308 @@ fn interact_with_cupboards(
     }
 }
 
-fn interact_with_kettles(
+fn interact_with_cup_shelf(
     mut player_interacted_events: EventReader<PlayerInteracted>,
+    shelves: Query<(), With<CupShelf>>,
+    mut cupboards: Query<&mut Cupboard>,
+    cups: Query<(&Cup, Option<&Dirty>)>,
+    mut cup_spawner: EventWriter<SpawnCupEvent>,
+    mut status_events: EventWriter<StatusEvent>,
+    mut commands: Commands,
+) {
+    for event in player_interacted_events.iter() {
+        if shelves.get(event.interacted_entity).is_err() {
+            continue;
+        }
+        let mut cupboard = cupboards.single_mut();
+        let message = match event.held_entity {
+            Some(held) => match cups.get(held) {
+                Ok((cup, dirty)) if dirty.is_some() || cup.tea.is_some() => {
+                    "That cup needs to be washed before it goes back.".to_string()
+                }
+                Ok(_) => {
+                    cupboard.cups += 1;
+                    despawn_held_entity(&mut commands, event.player_entity, held);
+                    format!("You put the cup away ({} left).", cupboard.cups)
+                }
+                Err(_) => "Your hands are full.".to_string(),
+            },
+            None if cupboard.cups > 0 => {
+                cupboard.cups -= 1;
+                cup_spawner.send(SpawnCupEvent);
+                format!("You take a cup ({} left).", cupboard.cups)
+            }
+            None => {
+                "No clean cups remaining. Wash some dirty ones at the sink.".to_string()
+            }
+        };
+        status_events.send(StatusEvent::timed_message(
+            event.player_entity,
+            message,
+            DEFAULT_EXPIRY,
+        ));
+    }
+}
+
+// Pours a single serving from a teapot that has been set down into the cup the
+// player is holding.
+fn pour_tea(
+    mut player_interacted_events: EventReader<PlayerInteracted>,
+    mut teapots: Query<&mut TeaPot, Without<Dirty>>,
+    mut cups: Query<&mut Cup, Without<Dirty>>,
+    mut status_events: EventWriter<StatusEvent>,
+    mut commands: Commands,
+    time: Res<Time>,
+) {
+    for event in player_interacted_events.iter() {
+        let mut teapot = match teapots.get_mut(event.interacted_entity) {
+            Ok(teapot) => teapot,
+            Err(_) => continue,
+        };
+        let mut cup = match event.held_entity.and_then(|held| cups.get_mut(held).ok()) {
+            Some(cup) => cup,
+            None => continue,
+        };
+        let message = if cup.tea.is_some() {
+            "That cup is already full.".to_owned()
+        } else if teapot.steeped_at.is_none() || teapot.water < CUP_WATER {
+            "There's no tea in that pot to pour.".to_owned()
+        } else {
+            //FIXME: wasm issues
+            teapot.steeped_for = Some(time.last_update().unwrap() - teapot.steeped_at.unwrap());
+            let mut serving = teapot.clone();
+            serving.water = CUP_WATER;
+            serving.covered = false;
+            teapot.water -= CUP_WATER;
+            if teapot.water < CUP_WATER {
+                commands.entity(event.interacted_entity).insert(Dirty);
+            }
+            cup.tea = Some(serving);
+            format!("You pour a cup of tea, with enough left for {} more.", teapot.water / CUP_WATER)
+        };
+        status_events.send(StatusEvent::timed_message(
+            event.player_entity,
+            message,
+            DEFAULT_EXPIRY,
+        ));
+    }
+}
+
+fn heat_kettles(
+    mut kettles: Query<(&mut Kettle, &mut Interactable)>,
+    time: Res<Time>,
+) {
+    for (mut kettle, mut interactable) in &mut kettles {
+        let delta = time.delta_seconds();
+        kettle.temperature = if kettle.on {
+            (kettle.temperature + KETTLE_HEATING_RATE * delta).min(BOILING_TEMPERATURE)
+        } else {
+            (kettle.temperature - KETTLE_COOLING_RATE * delta).max(ROOM_TEMPERATURE)
+        };
+        let message = kettle_message(&kettle);
+        if interactable.message != message {
+            interactable.message = message;
+        }
+    }
+}
+
+// Brewed tea loses heat to the room; tea that is being held or that has the lid
+// on keeps warm for longer.
+fn cool_tea(tea: &mut TeaPot, held: bool, delta: f32) {
+    if tea.water == 0 || tea.temperature <= ROOM_TEMPERATURE {
+        return;
+    }
+    let mut rate = if held { HELD_TEAPOT_COOLING_RATE } else { TEAPOT_COOLING_RATE };
+    if tea.covered {
+        rate *= COVERED_COOLING_FACTOR;
+    }
+    let loss = (tea.temperature - ROOM_TEMPERATURE) * rate * delta;
+    tea.temperature = (tea.temperature - loss).max(ROOM_TEMPERATURE);
+}
+
+fn cool_brewed_tea(
+    mut teapots: Query<(&mut TeaPot, Option<&Parent>)>,
+    mut cups: Query<(&mut Cup, Option<&Parent>)>,
+    time: Res<Time>,
+) {
+    for (mut teapot, held) in &mut teapots {
+        cool_tea(&mut teapot, held.is_some(), time.delta_seconds());
+    }
+    for (mut cup, held) in &mut cups {
+        if let Some(ref mut tea) = cup.tea {
+            cool_tea(tea, held.is_some(), time.delta_seconds());
+        }
+    }
+}
+
+fn cover_held_teapot(
+    keys: Res<Input<KeyCode>>,
+    player: Query<(Entity, &Holding), With<Player>>,
     mut teapots: Query<&mut TeaPot>,
+    mut status_events: EventWriter<StatusEvent>,
+) {
+    if !keys.just_released(KeyCode::L) {
+        return;
+    }
+    for (player_entity, holding) in &player {
+        let mut teapot = match teapots.get_mut(holding.entity) {
+            Ok(teapot) => teapot,
+            Err(_) => continue,
+        };
+        teapot.covered = !teapot.covered;
+        let message = if teapot.covered {
+            "You put the lid on the teapot to keep it warm."
+        } else {
+            "You take the lid off the teapot."
+        };
+        status_events.send(StatusEvent::timed_message(
+            player_entity,
+            message.to_owned(),
+            DEFAULT_EXPIRY,
+        ));
+    }
+}
+
+// Returns whether any of the units has gone from fresh to stale, or stale to spoiled.
+fn spoil(units: &mut [f32], ingredient: Ingredient, delta: f32) -> bool {
+    let shelf_life = match ingredient.shelf_life() {
+        Some(shelf_life) => shelf_life,
+        None => return false,
+    };
+    let loss = delta / shelf_life.as_secs_f32();
+    let mut turned = false;
+    for freshness in units {
+        let before = Freshness::from_value(*freshness);
+        *freshness = (*freshness - loss).max(0.);
+        turned |= Freshness::from_value(*freshness) != before;
+    }
+    turned
+}
+
+fn spoil_ingredients(
+    mut stashes: Query<(&mut TeaStash, &mut Interactable)>,
+    mut players: Query<&mut Player>,
+    time: Res<Time>,
+) {
+    // Freshness drifts every frame, but nobody notices until an ingredient turns.
+    for (mut stash, mut interactable) in &mut stashes {
+        let ingredient = stash.ingredient;
+        if !spoil(&mut stash.bypass_change_detection().units, ingredient, time.delta_seconds()) {
+            continue;
+        }
+        stash.set_changed();
+        let message = stash_message(&stash);
+        if interactable.message != message {
+            interactable.message = message;
+        }
+    }
+    for mut player in &mut players {
+        let mut turned = false;
+        for (ingredient, units) in player.bypass_change_detection().carrying.iter_mut() {
+            turned |= spoil(units, *ingredient, time.delta_seconds());
+        }
+        if turned {
+            player.set_changed();
+        }
+    }
+}
+
+fn stash_message(stash: &TeaStash) -> String {
+    match stash.freshness() {
+        Some(freshness) => format!(
+            "Press X to measure out {:?} ({})",
+            stash.ingredient,
+            freshness.adjective(),
+        ),
+        None => format!("Press X to measure out {:?}", stash.ingredient),
+    }
+}
+
+fn kettle_message(kettle: &Kettle) -> String {
+    let state = if kettle.on { "on" } else { "off" };
+    format!(
+        "The kettle is {} ({:.0}°C). Press X to fill the pot or stir in additions, or to switch it {}.",
+        state,
+        kettle.temperature,
+        if kettle.on { "off" } else { "on" },
+    )
+}
+
+fn describe_water(temperature: f32) -> &'static str {
+    if temperature >= BOILING_TEMPERATURE - 2. {
+        "boiling"
+    } else if temperature >= 80. {
+        "hot"
+    } else if temperature >= 50. {
+        "warm"
+    } else {
+        "lukewarm"
+    }
+}
+
+// Removes the steepable ingredients from the player's hands, leaving any additives.
+fn take_leaves(player: &mut Player) -> Vec<(Ingredient, Vec<f32>)> {
+    let leaves = player.carrying
+        .keys()
+        .filter(|ingredient| !ingredient.is_additive())
+        .cloned()
+        .collect::<Vec<_>>();
+    leaves
+        .into_iter()
+        .map(|ingredient| (ingredient, player.carrying.remove(&ingredient).unwrap()))
+        .collect()
+}
+
+fn add_to_teapot(teapot: &mut TeaPot, ingredient: Ingredient, units: &[f32]) {
+    *teapot.ingredients.entry(ingredient).or_insert(0) += units.len() as u32;
+    for freshness in units {
+        teapot.ingredient_freshness = teapot.ingredient_freshness.min(*freshness);
+    }
+}
+
+fn interact_with_kettles(
+    mut player_interacted_events: EventReader<PlayerInteracted>,
+    mut teapots: Query<(&mut TeaPot, Option<&Dirty>)>,
     mut player: Query<&mut Player>,
-    kettles: Query<&Kettle>,
+    mut kettles: Query<&mut Kettle>,
     mut status_events: EventWriter<StatusEvent>,
     time: Res<Time>,
 ) {
     for event in player_interacted_events.iter() {
         let mut player = player.get_mut(event.player_entity).unwrap();
-        let _kettle = match kettles.get(event.interacted_entity) {
+        let mut kettle = match kettles.get_mut(event.interacted_entity) {
             Ok(result) => result,
             Err(_) => continue,
         };
+        let pending_refill = kettle.pending_refill.take();
+        // Interacting with empty hands toggles the kettle.
         let held_entity = match event.held_entity {
             Some(entity) => entity,
             None => {
+                kettle.on = !kettle.on;
+                let message = if kettle.on {
+                    format!("You switch on the kettle ({:.0}°C).", kettle.temperature)
+                } else {
+                    format!("You switch off the kettle ({:.0}°C).", kettle.temperature)
+                };
                 status_events.send(StatusEvent::timed_message(
                     event.player_entity,
-                    "You need a teapot to use the kettle.".to_owned(),
+                    message,
                     DEFAULT_EXPIRY,
                 ));
                 continue;
             }
         };
         let mut teapot = match teapots.get_mut(held_entity) {
-            Ok(teapot) => teapot,
+            Ok((_, Some(_dirty))) => {
+                status_events.send(StatusEvent::timed_message(
+                    event.player_entity,
+                    "This teapot is dirty. Wash it at the sink first.".to_owned(),
+                    DEFAULT_EXPIRY,
+                ));
+                continue;
+            }
+            Ok((teapot, None)) => teapot,
             Err(_) => {
                 status_events.send(StatusEvent::timed_message(
                     event.player_entity,
@@ -192,19 +997,60 @@ fn interact_with_kettles(
             }
         };
 
-        let message = if !player.carrying.is_empty() {
-            let ingredients = player
-                .carrying
-                .keys()
-                .map(|k| format!("{:?}", k))
-                .collect::<Vec<_>>();
+        let carrying_additives = !player.additives.is_empty();
+        let carrying_leaves = player.carrying.keys().any(|ingredient| !ingredient.is_additive());
+        let message = if teapot.water > 0 && carrying_additives {
+            // Additives go in one at a time, so the player decides the order.
+            let ingredient = player.additives.remove(0);
+            let units = player.carrying.remove(&ingredient).unwrap();
+            add_to_teapot(&mut teapot, ingredient, &units);
+            if !teapot.additions.contains(&ingredient) {
+                teapot.additions.push(ingredient);
+            }
+            match player.additives.first() {
+                Some(next) => format!(
+                    "You stir the {:?} into the tea. Press X again to stir in the {:?}.",
+                    ingredient,
+                    next,
+                ),
+                None => format!("You stir the {:?} into the tea.", ingredient),
+            }
+        } else if carrying_leaves && !teapot.is_empty() && pending_refill != Some(held_entity) {
+            kettle.pending_refill = Some(held_entity);
+            format!(
+                "This teapot already holds {}. Press X again to pour it away and refill it.",
+                teapot.describe_ingredients(),
+            )
+        } else if carrying_leaves {
+            let leaves = take_leaves(&mut player);
+            let names = leaves
+                .iter()
+                .map(|(ingredient, _)| format!("{:?}", ingredient))
+                .collect::<Vec<_>>()
+                .join(" and the ");
 
-            teapot.water = 100;
-            teapot.ingredients = std::mem::take(&mut player.carrying);
-            teapot.steeped_at = Some(time.last_update().unwrap());
+            *teapot = TeaPot {
+                water: 100,
+                water_temperature: kettle.temperature,
+                temperature: kettle.temperature,
+                steeped_at: Some(time.last_update().unwrap()),
+                ..default()
+            };
+            for (ingredient, units) in leaves {
+                add_to_teapot(&mut teapot, ingredient, &units);
+            }
 
-            let ingredients = ingredients.join(" and the ");
-            format!("You add the {} to the teapot and fill it with boiling water.", ingredients)
+            let mut message = format!(
+                "You add the {} to the teapot and fill it with {} water.",
+                names,
+                describe_water(kettle.temperature),
+            );
+            if carrying_additives {
+                message += " Stir in the additions once it has steeped.";
+            }
+            message
+        } else if carrying_additives {
+            "Additions go in once the tea has steeped. You need leaves to steep first.".to_owned()
         } else {
             "You need ingredients to steep before adding the water.".to_owned()
         };
@@ -296,7 +1142,7 @@ fn spawn_teapot(
             TeaPot::default(),
             Item,
             Interactable {
-                message: "Press X to collect".to_string(),
+                message: "Press X to collect, then I to look inside".to_string(),
                 ..default()
             },
             movable,
@@ -314,6 +1160,43 @@ fn spawn_teapot(
     }
 }
 
+struct SpawnCupEvent;
+
+fn spawn_cup(
+    mut events: EventReader<SpawnCupEvent>,
+    texture: Res<TeapotTexture>,
+    mut commands: Commands,
+) {
+    for _event in events.iter() {
+        let size = Vec2::new(TILE_SIZE / 2., TILE_SIZE / 2.);
+        let sprite = SpriteBundle {
+            sprite: Sprite {
+                color: CUP_TINT,
+                custom_size: Some(size),
+                ..default()
+            },
+            texture: texture.0.clone(),
+            transform: Transform::from_translation(Vec3::new(0., 0., 0.9)),
+            ..default()
+        };
+        let sized = HasSize {
+            size: MapSize { width: 1, height: 1 },
+        };
+
+        commands.spawn((
+            Cup::default(),
+            Item,
+            Interactable {
+                message: "Press X to collect".to_string(),
+                ..default()
+            },
+            sized,
+            sprite,
+            AutoPickUp,
+        ));
+    }
+}
+
 pub fn spawn_cupboard(
     commands: &mut Commands,
     movable: Movable,
@@ -322,7 +1205,10 @@ pub fn spawn_cupboard(
 ) {
     let mut rng = rand::thread_rng();
     commands.spawn((
-        Cupboard { teapots: rng.gen_range(4..10) },
+        Cupboard {
+            teapots: rng.gen_range(2..4),
+            cups: rng.gen_range(6..10),
+        },
         Interactable {
             message: "Press X to pick up teapot".to_string(),
             ..default()
@@ -333,16 +1219,36 @@ pub fn spawn_cupboard(
     ));
 }
 
+pub fn spawn_cup_shelf(
+    commands: &mut Commands,
+    movable: Movable,
+    sized: HasSize,
+    transform: Transform,
+) {
+    commands.spawn((
+        CupShelf,
+        Interactable {
+            message: "Press X to take a cup".to_string(),
+            ..default()
+        },
+        movable,
+        sized,
+        transform,
+    ));
+}
+
 pub fn spawn_kettle(
     commands: &mut Commands,
     movable: Movable,
     sized: HasSize,
     transform: Transform,
 ) {
+    let kettle = Kettle::default();
+    let message = kettle_message(&kettle);
     commands.spawn((
-        Kettle,
+        kettle,
         Interactable {
-            message: "Press X to fill the pot".to_string(),
+            message,
             ..default()
         },
         movable,
@@ -351,6 +1257,21 @@ pub fn spawn_kettle(
     ));
 }
 
+pub fn teastash_components(ingredient: Ingredient, amount: u32) -> (TeaStash, Interactable) {
+    let stash = TeaStash {
+        ingredient,
+        units: vec![1.; amount as usize],
+    };
+    let message = stash_message(&stash);
+    (
+        stash,
+        Interactable {
+            message,
+            ..default()
+        },
+    )
+}
+
 pub fn spawn_teastash(
     commands: &mut Commands,
     movable: Movable,
@@ -359,12 +1280,10 @@ pub fn spawn_teastash(
     ingredient: Ingredient,
     amount: u32,
 ) {
+    let (stash, interactable) = teastash_components(ingredient, amount);
     commands.spawn((
-        TeaStash { ingredient, amount },
-        Interactable {
-            message: format!("Press X to pick up {:?}", ingredient),
-            ..default()
-        },
+        stash,
+        interactable,
         movable,
         sized,
         transform,
@@ -377,10 +1296,12 @@ pub fn spawn_sink(
     sized: HasSize,
     transform: Transform,
 ) {
+    let sink = Sink::default();
+    let message = sink_message(&sink);
     commands.spawn((
-        Sink,
+        sink,
         Interactable {
-            message: "Press X to clean and put away pot.".to_owned(),
+            message,
             ..default()
         },
         movable,
@@ -388,3 +1309,79 @@ pub fn spawn_sink(
         transform,
     ));
 }
+
+#[test]
+fn brew_quality_from_steep_time() {
+    let mut teapot = TeaPot::default();
+    teapot.ingredients.insert(Ingredient::GreenTea, 2);
+    teapot.ingredients.insert(Ingredient::Honey, 1);
+
+    teapot.steeped_for = Some(Duration::from_secs(5));
+    assert_eq!(teapot.brew_quality(), Some(BrewQuality::UnderSteeped));
+    teapot.steeped_for = Some(Duration::from_secs(15));
+    assert_eq!(teapot.brew_quality(), Some(BrewQuality::Perfect));
+    teapot.steeped_for = Some(Duration::from_secs(60));
+    assert_eq!(teapot.brew_quality(), Some(BrewQuality::Bitter));
+}
+
+#[test]
+fn steep_window_overlap() {
+    let mut teapot = TeaPot::default();
+    teapot.ingredients.insert(Ingredient::BlackTea, 1);
+    teapot.ingredients.insert(Ingredient::GreenTea, 1);
+    assert_eq!(
+        teapot.steep_window(),
+        Some((Duration::from_secs(20), Duration::from_secs(25))),
+    );
+
+    teapot.ingredients.insert(Ingredient::Matcha, 1);
+    assert_eq!(
+        teapot.steep_window(),
+        Some((Duration::from_secs(20), Duration::from_secs(20))),
+    );
+}
+
+#[test]
+fn water_quality_from_temperature() {
+    let mut teapot = TeaPot::default();
+    teapot.ingredients.insert(Ingredient::GreenTea, 1);
+    teapot.water = 100;
+
+    teapot.water_temperature = 60.;
+    assert_eq!(teapot.water_quality(), Some(WaterQuality::TooCool));
+    teapot.water_temperature = 80.;
+    assert_eq!(teapot.water_quality(), Some(WaterQuality::Ideal));
+    teapot.water_temperature = BOILING_TEMPERATURE;
+    assert_eq!(teapot.water_quality(), Some(WaterQuality::TooHot));
+}
+
+#[test]
+fn serving_temperature_thresholds() {
+    let mut teapot = TeaPot::default();
+    assert_eq!(teapot.serving_temperature(), None);
+
+    teapot.water = 100;
+    teapot.temperature = 90.;
+    assert_eq!(teapot.serving_temperature(), Some(ServingTemperature::Hot));
+    teapot.temperature = 45.;
+    assert_eq!(teapot.serving_temperature(), Some(ServingTemperature::Lukewarm));
+    teapot.temperature = ROOM_TEMPERATURE;
+    assert_eq!(teapot.serving_temperature(), Some(ServingTemperature::Cold));
+}
+
+#[test]
+fn ingredients_spoil_at_their_own_rate() {
+    let mut milk = vec![1.; 2];
+    assert!(!spoil(&mut milk, Ingredient::Milk, 60.));
+    assert_eq!(Freshness::from_value(milk[0]), Freshness::Fresh);
+    assert!(spoil(&mut milk, Ingredient::Milk, 90.));
+    assert_eq!(Freshness::from_value(milk[1]), Freshness::Stale);
+
+    let mut leaves = vec![1.];
+    spoil(&mut leaves, Ingredient::BlackTea, 150.);
+    assert_eq!(Freshness::from_value(leaves[0]), Freshness::Fresh);
+
+    let mut sugar = vec![1.];
+    assert!(!spoil(&mut sugar, Ingredient::Sugar, 10000.));
+    assert_eq!(sugar, vec![1.]);
+}
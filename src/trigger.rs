@@ -2,8 +2,10 @@
 
 use bevy::prelude::*;
 use crate::action::*;
+use crate::entity::RelationshipStatus;
 use crate::interaction::PlayerInteracted;
 use crate::message_line::StatusEvent;
+use crate::personality::{PersonalityId, RelationshipMilestoneEvent};
 use crate::player::{Player, Holding};
 use crate::tea::SpawnTeapotEvent;
 use std::default::Default;
@@ -20,6 +22,7 @@ impl Plugin for TriggerPlugin {
             .add_system(process_triggers)
             .add_system(process_proximity)
             .add_system(process_interacted)
+            .add_system(process_milestones)
             .add_system(run_immediate_actions);
     }
 }
@@ -29,6 +32,7 @@ pub enum TriggerCondition {
     Manual,
     PlayerProximity(Entity),
     PlayerInteract(Entity),
+    RelationshipMilestone(PersonalityId, RelationshipStatus),
 }
 
 pub struct Trigger {
@@ -54,6 +58,17 @@ impl Trigger {
         Self::with_condition(label.into(), TriggerCondition::PlayerInteract(entity))
     }
 
+    pub fn relationship_milestone<T: Into<String>>(
+        label: T,
+        personality: PersonalityId,
+        status: RelationshipStatus,
+    ) -> Trigger {
+        Self::with_condition(
+            label.into(),
+            TriggerCondition::RelationshipMilestone(personality, status),
+        )
+    }
+
     pub fn action(mut self, action: Action) -> Trigger {
         self.actions.push(Box::new(action));
         Trigger {
@@ -107,6 +122,22 @@ fn process_interacted(
     }
 }
 
+fn process_milestones(
+    mut milestone_events: EventReader<RelationshipMilestoneEvent>,
+    mut trigger_events: EventWriter<TriggerEvent>,
+    triggers: Res<Triggers>,
+) {
+    for event in milestone_events.iter() {
+        for trigger in &triggers.0 {
+            if let TriggerCondition::RelationshipMilestone(id, status) = &trigger.condition {
+                if *id == event.personality && *status == event.status {
+                    trigger_events.send(TriggerEvent(trigger.label.clone(), None))
+                }
+            }
+        }
+    }
+}
+
 pub struct TriggerEvent(pub String, pub Option<Entity>);
 
 fn process_triggers(